@@ -0,0 +1,39 @@
+use std::env;
+
+use burraco::difficulty::Difficulty;
+
+const USAGE: &str = "usage: calibrate [--games N]";
+
+/// Measures the win rate of every difficulty against the smart agent, for
+/// `Difficulty::calibrated_win_rate`, failing when one has drifted from it
+fn main() -> Result<(), String> {
+    let mut args = env::args().skip(1);
+    let mut num_games = 1000;
+    while let Some(arg) = args.next() {
+        if arg == "--games" {
+            let games = args.next().ok_or(USAGE)?;
+            num_games = games.parse().map_err(|_| USAGE)?;
+        } else {
+            return Err(USAGE.into());
+        }
+    }
+
+    let mut drifted = Vec::new();
+    for level in Difficulty::ALL.iter() {
+        let win_rate = level.measure_win_rate(0..num_games)?;
+        println!(
+            "{}: {:.1}% (calibrated {:.1}%)",
+            level,
+            win_rate * 100.0,
+            level.calibrated_win_rate() * 100.0
+        );
+        if let Err(reason) = level.check_win_rate(win_rate, num_games) {
+            drifted.push(reason);
+        }
+    }
+    if drifted.is_empty() {
+        Ok(())
+    } else {
+        Err(drifted.join("\n"))
+    }
+}
@@ -0,0 +1,38 @@
+use std::env;
+use std::fs::File;
+use std::io::BufWriter;
+
+use burraco::agent::AgentType;
+use burraco::tournament::dump_dataset;
+
+const USAGE: &str = "usage: dataset [--games N] TEAM TEAM [TEAM] OUT_FILE
+  TEAM as for the tournament example";
+
+/// Writes the (features, action, outcome) rows of seeded 1 vs 1 (vs 1) games as CSV
+fn main() -> Result<(), String> {
+    let mut args = env::args().skip(1);
+    let mut num_games = 100;
+    let mut names = vec![];
+    while let Some(arg) = args.next() {
+        if arg == "--games" {
+            let games = args.next().ok_or(USAGE)?;
+            num_games = games.parse().map_err(|_| USAGE)?;
+        } else {
+            names.push(arg);
+        }
+    }
+    let out = names.pop().ok_or(USAGE)?;
+    if !(2..=3).contains(&names.len()) {
+        return Err(USAGE.into());
+    }
+
+    let agents = names
+        .iter()
+        .map(|name| name.parse())
+        .collect::<Result<Vec<_>, String>>()?;
+    let teams: Vec<&[AgentType]> = agents.iter().map(std::slice::from_ref).collect();
+    let file = File::create(&out).map_err(|e| format!("{}: {}", out, e))?;
+    let rows = dump_dataset(&teams, 0..num_games, &mut BufWriter::new(file))?;
+    println!("{} rows of {} games written to {}", rows, num_games, out);
+    Ok(())
+}
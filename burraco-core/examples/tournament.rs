@@ -0,0 +1,37 @@
+use std::env;
+
+use burraco::agent::AgentType;
+use burraco::tournament::win_counts;
+
+const USAGE: &str = "usage: tournament [--games N] TEAM TEAM [TEAM]
+  TEAM is one of dumb, smart, max, lookahead, expert, a difficulty as in level-easy,
+  a .policy file for a LinearPolicyAgent or else a weights file for a WeightedAgent";
+
+/// Plays seeded 1 vs 1 (vs 1) games between the teams and prints how many each won
+fn main() -> Result<(), String> {
+    let mut args = env::args().skip(1);
+    let mut num_games = 100;
+    let mut names = vec![];
+    while let Some(arg) = args.next() {
+        if arg == "--games" {
+            let games = args.next().ok_or(USAGE)?;
+            num_games = games.parse().map_err(|_| USAGE)?;
+        } else {
+            names.push(arg);
+        }
+    }
+    if !(2..=3).contains(&names.len()) {
+        return Err(USAGE.into());
+    }
+
+    let agents = names
+        .iter()
+        .map(|name| name.parse())
+        .collect::<Result<Vec<_>, String>>()?;
+    let teams: Vec<&[AgentType]> = agents.iter().map(std::slice::from_ref).collect();
+    let wins = win_counts(&teams, 0..num_games)?;
+    for (name, wins) in names.iter().zip(wins.iter()) {
+        println!("{}: {} of {} games", name, wins, num_games);
+    }
+    Ok(())
+}
@@ -0,0 +1,45 @@
+use std::env;
+use std::fs;
+
+use burraco::tuning::tune;
+use burraco::tuning::TuningConfig;
+use burraco::weights::Weights;
+
+const USAGE: &str =
+    "usage: tune [--seed N] [--rounds N] [--games N] [--players N] [--start FILE] OUT_FILE";
+
+/// Tunes the weights of the WeightedAgent through self-play and writes the best to OUT_FILE
+fn main() -> Result<(), String> {
+    let mut args = env::args().skip(1);
+    let mut config = TuningConfig::default();
+    let mut start = Weights::default();
+    let mut out = None;
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| USAGE.to_string());
+        match arg.as_str() {
+            "--seed" => config.seed = value()?.parse().map_err(|_| USAGE)?,
+            "--rounds" => config.rounds = value()?.parse().map_err(|_| USAGE)?,
+            "--games" => config.games = value()?.parse().map_err(|_| USAGE)?,
+            "--players" => config.num_team_players = value()?.parse().map_err(|_| USAGE)?,
+            "--start" => start = Weights::load(value()?)?,
+            _ if out.is_none() && !arg.starts_with("--") => out = Some(arg),
+            _ => return Err(USAGE.into()),
+        }
+    }
+    let out = out.ok_or(USAGE)?;
+
+    let best = tune(start, &config, |round, best, rate| {
+        println!("round {}: candidate won {:.1}%", round, 100.0 * rate);
+        // the candidate became the best
+        if rate > config.min_win_rate {
+            print!("{}", best);
+        }
+    })?;
+    let header = format!(
+        "# tuned from {} rounds of {} games per side, seed {}\n",
+        config.rounds, config.games, config.seed
+    );
+    fs::write(&out, header + &best.to_string()).map_err(|e| format!("{}: {}", out, e))?;
+    println!("best weights written to {}", out);
+    Ok(())
+}
@@ -0,0 +1,103 @@
+#![no_main]
+use std::convert::TryInto;
+
+use libfuzzer_sys::fuzz_target;
+
+use burraco::actions::BurracoGame;
+use burraco::actions::DiscardAction;
+use burraco::actions::DrawAction;
+use burraco::actions::GamePhase;
+use burraco::actions::PlayAction;
+use burraco::model::BurracoState;
+use burraco::model::Cards;
+use burraco::rules::DealStyle;
+use burraco::rules::DiscardRules;
+use burraco::rules::OpenPileDraw;
+use burraco::rules::Rules;
+use burraco::rules::TableVariant;
+
+/// One bit or small number of the byte per rule
+fn rules_from(byte: u8) -> Rules {
+    let mut rules = if byte & 1 == 0 {
+        Rules::default()
+    } else {
+        Rules::brazilian()
+    };
+    rules.table = match (byte >> 1) % 3 {
+        0 => TableVariant::OneVsOne,
+        1 => TableVariant::OneVsOneVsOne,
+        _ => TableVariant::TwoVsTwo,
+    };
+    rules.open_pile_draw = match (byte >> 3) % 3 {
+        0 => OpenPileDraw::WholePile,
+        1 => OpenPileDraw::TopCardOnly,
+        _ => OpenPileDraw::WholePileMustUseTop,
+    };
+    if byte & 32 != 0 {
+        rules.discard = DiscardRules::federation();
+    }
+    if byte & 64 != 0 {
+        rules.deal.style = DealStyle::Alternating;
+    }
+    rules.deal.cut = byte & 128 != 0;
+    rules
+}
+
+fn check(game: &BurracoGame) {
+    if let Err(reason) = game.check_invariants() {
+        panic!("Broken game invariant: {}", reason);
+    }
+}
+
+// The first 8 bytes seed the deal and the next picks the rules, every byte after that picks
+// an action. Draws and discards may be refused, which should leave the game as it was.
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 9 {
+        return;
+    }
+    let (head, choices) = data.split_at(9);
+    let seed = u64::from_le_bytes(head[..8].try_into().expect("8 bytes"));
+    let state = BurracoState::init_with_rules(rules_from(head[8]), Some(seed));
+    let mut game = BurracoGame::from(state);
+    let deck = Cards::build_deck(1);
+
+    let mut moves_allowed = 0;
+    for choice in choices.iter().map(|c| *c as usize) {
+        match game.phase() {
+            GamePhase::Draw => {
+                let action = if choice % 2 == 0 {
+                    DrawAction::DrawPile
+                } else {
+                    DrawAction::DrawOpen
+                };
+                if game.draw(action).is_err() {
+                    check(&game);
+                    game.draw(DrawAction::DrawPile)
+                        .expect("drawing from the draw pile is always allowed");
+                }
+                moves_allowed = game.current_team().played_runs.len();
+            }
+            GamePhase::Play => {
+                let actions = game.play_actions(moves_allowed);
+                let (action, _score) = actions[choice % actions.len()].clone();
+                if let PlayAction::MoveCard(_, _, _) = action {
+                    moves_allowed -= 1;
+                }
+                if let Err(error) = game.play(action.clone()) {
+                    panic!("Enumerated action {:?} refused: {}", action, error);
+                }
+            }
+            GamePhase::Discard => {
+                let card = deck[choice % deck.len()];
+                if game.discard(DiscardAction(card)).is_err() {
+                    check(&game);
+                    let card = game.discard_choices()[0];
+                    game.discard(DiscardAction(card))
+                        .expect("the discard choices are allowed");
+                }
+            }
+            GamePhase::Finished(_) => break,
+        }
+        check(&game);
+    }
+});
@@ -1,14 +1,18 @@
-use std::usize;
-
+use crate::error::BurracoError;
 use crate::model::Append;
 use crate::model::BurracoState;
 use crate::model::Card;
+use crate::model::CardCounts;
 use crate::model::Cards;
 use crate::model::Player;
+use crate::model::PlayerView;
 use crate::model::Rank::*;
 use crate::model::Run;
 use crate::model::RunType;
 use crate::model::Team;
+use crate::rules::FeedingDiscard;
+use crate::rules::OpenPileDraw;
+use crate::rules::RunRules;
 use PlayAction::*;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -19,19 +23,127 @@ pub enum GamePhase {
     Finished(usize), // winning team
 }
 
+#[derive(Clone)]
 pub struct BurracoGame {
     state: BurracoState,
     phase: GamePhase,
+    /// the cards the game started with, see `check_invariants`
+    original_cards: CardCounts,
 }
 
 impl BurracoGame {
     pub fn from(state: BurracoState) -> BurracoGame {
         BurracoGame {
+            original_cards: state.all_cards().deck_counts(),
             state,
             phase: GamePhase::Draw,
         }
     }
 
+    /// A game from a position in the middle of a turn, see `ScenarioBuilder`
+    pub fn at_phase(state: BurracoState, phase: GamePhase) -> BurracoGame {
+        BurracoGame {
+            phase,
+            ..BurracoGame::from(state)
+        }
+    }
+
+    /// Check that the game is consistent, done after every action in debug builds
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let state = &self.state;
+
+        let counts = state.all_cards().deck_counts();
+        if counts != self.original_cards {
+            return Err(format!(
+                "Cards changed since the start of the game: {}",
+                Cards::count_mismatches(&self.original_cards, &counts).join(", ")
+            ));
+        }
+
+        for (t, team) in state.teams.iter().enumerate() {
+            for (i, run) in team.played_runs.iter().enumerate() {
+                let rebuilt = match run.run_type() {
+                    RunType::Sequence => {
+                        Run::build_sequence_run_with(run.cards().clone(), run.rules())
+                    }
+                    RunType::Group => Run::build_group_run_with(run.cards().clone(), run.rules()),
+                };
+                if rebuilt.as_ref() != Ok(run) {
+                    return Err(format!("Run {} of team {} is not valid: {}", i, t, run));
+                }
+            }
+        }
+
+        let num_players: usize = state.teams.iter().map(|t| t.players.len()).sum();
+        let mut seats = state.player_team_idxs.clone();
+        seats.sort_unstable();
+        seats.dedup();
+        if seats.len() != state.player_team_idxs.len()
+            || seats.len() != num_players
+            || seats
+                .iter()
+                .any(|(t, p)| *t >= state.teams.len() || *p >= state.teams[*t].players.len())
+        {
+            return Err(format!(
+                "Seats {:?} do not match the players",
+                state.player_team_idxs
+            ));
+        }
+        if state.player_turn >= num_players {
+            return Err(format!("No player at turn {}", state.player_turn));
+        }
+
+        if !matches!(self.phase, GamePhase::Finished(_)) {
+            if let Some((t, p)) = seats
+                .iter()
+                .find(|(t, p)| state.teams[*t].players[*p].hand.is_empty())
+            {
+                return Err(format!(
+                    "Player {}-{} has no cards while the game goes on",
+                    t, p
+                ));
+            }
+        }
+
+        let pot_sizes = state.rules.table.pot_sizes();
+        if state.pots.len() != pot_sizes.len() {
+            return Err(format!(
+                "{} pots, expected {}",
+                state.pots.len(),
+                pot_sizes.len()
+            ));
+        }
+        for (i, (pot, size)) in state.pots.iter().zip(pot_sizes).enumerate() {
+            if !pot.is_empty() && pot.len() != size {
+                return Err(format!(
+                    "Pot {} has {} cards, expected {}",
+                    i,
+                    pot.len(),
+                    size
+                ));
+            }
+        }
+        let pots_taken = state.pots.iter().filter(|p| p.is_empty()).count();
+        let teams_reached = state.teams.iter().filter(|t| t.has_reached_pot).count();
+        if pots_taken != teams_reached {
+            return Err(format!(
+                "{} pots taken, but {} teams have reached a pot",
+                pots_taken, teams_reached
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Runs `check_invariants` in debug builds, tests included
+    fn debug_check_invariants(&self) {
+        if cfg!(debug_assertions) {
+            if let Err(reason) = self.check_invariants() {
+                panic!("Broken game invariant: {}", reason);
+            }
+        }
+    }
+
     pub fn current_player(&self) -> &Player {
         let (team, player) = self.state.player_team_idxs[self.state.player_turn];
         &self.state.teams[team].players[player]
@@ -50,22 +162,31 @@ impl BurracoGame {
         self.phase
     }
 
-    pub fn draw(&mut self, action: DrawAction) -> Result<(), String> {
+    pub fn draw(&mut self, action: DrawAction) -> Result<(), BurracoError> {
         let (team, player) = self.state.player_team_idxs[self.state.player_turn];
 
         if self.phase != GamePhase::Draw {
-            return Err(format!(
-                "Draw action invalid when phase is: {:?}",
-                self.phase
-            ));
+            return Err(BurracoError::WrongPhase(self.phase));
         }
 
         match action {
             DrawAction::DrawOpen => {
+                DrawAction::check_draw_open(&self.state)?;
                 let open_pile = &mut self.state.open_pile;
+                let mut drawn = match self.state.rules.open_pile_draw {
+                    OpenPileDraw::WholePile => open_pile.drain_back(open_pile.len()),
+                    OpenPileDraw::TopCardOnly => open_pile.drain_back(1),
+                    OpenPileDraw::WholePileMustUseTop => {
+                        self.state.must_use = open_pile.last().cloned();
+                        open_pile.drain_back(open_pile.len())
+                    }
+                };
+                if drawn.len() == 1 {
+                    self.state.picked_single = Some(drawn[0]);
+                }
                 self.state.teams[team].players[player]
                     .hand
-                    .append(open_pile);
+                    .append(&mut drawn);
             }
             DrawAction::DrawPile => {
                 let draw_pile = &mut self.state.draw_pile;
@@ -78,11 +199,11 @@ impl BurracoGame {
         self.state.teams[team].players[player].hand.sort();
         if self.state.draw_pile.is_empty() {
             self.phase = GamePhase::Finished(self.winning_team()); // TODO calculate winner
-            Ok(())
         } else {
             self.phase = GamePhase::Play;
-            Ok(())
         }
+        self.debug_check_invariants();
+        Ok(())
     }
 
     fn cards_in_hand(cards: &Cards, player: &Player) -> bool {
@@ -96,104 +217,243 @@ impl BurracoGame {
         })
     }
 
-    fn remove_from_hand(player: &mut Player, cards: &Cards) -> Result<(), String> {
+    fn remove_from_hand(player: &mut Player, cards: &Cards) -> Result<(), BurracoError> {
         let hand = &mut player.hand;
         for card in cards.iter() {
             let index = hand
                 .iter()
                 .position(|c| c == card)
-                .ok_or_else(|| "Cannot remove card that does not exist".to_string())?;
+                .ok_or_else(|| BurracoError::CardsNotInHand(cards.clone()))?;
             hand.remove(index);
         }
         Ok(())
     }
 
-    pub fn play(&mut self, action: PlayAction) -> Result<(), String> {
+    fn team_run(&self, run_idx: usize) -> Result<&Run, BurracoError> {
+        self.current_team()
+            .played_runs
+            .get(run_idx)
+            .ok_or(BurracoError::InvalidRunIndex(run_idx))
+    }
+
+    /// The valid play actions found by `PlayAction::enumerate` for the current player
+    pub fn play_actions(&self, moves_allowed: usize) -> Vec<(PlayAction, i32)> {
+        let mut actions = PlayAction::enumerate(
+            &self.state.rules.run,
+            &self.current_team().played_runs,
+            &self.current_player().hand,
+            moves_allowed,
+        );
+        if let Some(card) = self.state.must_use {
+            actions.retain(|(a, _d_score)| a.cards_played().contains(&card));
+        }
+        let hand = &self.current_player().hand;
+        actions.retain(|(a, _d_score)| {
+            BurracoGame::check_closing(&self.state, hand, &a.cards_played()).is_ok()
+        });
+        if self.state.rules.close_needs_burraco {
+            actions.retain(|(a, _d_score)| {
+                !matches!(self.validate(a), Err(BurracoError::NoBurracoToClose))
+            });
+        }
+        actions
+    }
+
+    /// The partner the current player may ask "may I close?", as in `player_turn`
+    pub fn close_query_partner(&self) -> Result<usize, BurracoError> {
+        if !self.state.rules.close_query {
+            return Err(BurracoError::InvalidMove(
+                "The rules do not allow asking to close".into(),
+            ));
+        }
         if self.phase != GamePhase::Play {
-            return Err(format!(
-                "Play action invalid when phase is: {:?}",
-                self.phase
+            return Err(BurracoError::WrongPhase(self.phase));
+        }
+        if self.state.close_answer.is_some() {
+            return Err(BurracoError::InvalidMove(
+                "Already asked to close this turn".into(),
             ));
         }
+        if self.current_player().hand.len() < 2 {
+            // a no would leave nothing to discard
+            return Err(BurracoError::InvalidMove(
+                "Too few cards left to ask".into(),
+            ));
+        }
+        let team = self.state.curr_team();
+        (0..self.state.player_team_idxs.len())
+            .find(|p| *p != self.state.player_turn && self.state.player_team_idxs[*p].0 == team)
+            .ok_or_else(|| BurracoError::InvalidMove("No partner to ask".into()))
+    }
 
-        let (team, player) = self.state.player_team_idxs[self.state.player_turn];
+    /// Ask the partner "may I close?", `answer` gets the partner and what they see and gives
+    /// their answer. After a no the player has to keep a card after discarding.
+    pub fn ask_close<F>(&mut self, answer: F) -> Result<bool, BurracoError>
+    where
+        F: FnOnce(usize, &PlayerView) -> bool,
+    {
+        let partner_player = self.close_query_partner()?;
+        let may_close = answer(partner_player, &self.state.player_view(partner_player));
+        self.state.close_answer = Some(may_close);
+        self.debug_check_invariants();
+        Ok(may_close)
+    }
 
-        match action {
-            Noop => self.phase = GamePhase::Discard,
-            StartRun(run) => {
-                let all_contained_in_hand =
-                    BurracoGame::cards_in_hand(run.cards(), self.current_player());
+    /// Whether emptying the hand of the current player would end the game
+    fn closes_game(state: &BurracoState) -> bool {
+        state.teams[state.curr_team()].has_reached_pot || state.pots.iter().all(|p| p.is_empty())
+    }
 
-                if !all_contained_in_hand {
-                    return Err("Cannot place cards not in hand!".into());
-                }
+    /// The penalty for discarding `card` as the last card of the hand, the "scarto", by the
+    /// feeding rule
+    fn last_card_penalty(state: &BurracoState, card: &Card) -> Result<i32, BurracoError> {
+        match state.rules.discard.feeding {
+            FeedingDiscard::Allowed => Ok(0),
+            _ if !DiscardAction(*card).feeds_next_opponent(state) => Ok(0),
+            FeedingDiscard::Forbidden => Err(BurracoError::FeedsOpponent(*card)),
+            FeedingDiscard::Penalty(points) => Ok(points),
+        }
+    }
 
-                BurracoGame::remove_from_hand(
-                    &mut self.state.teams[team].players[player],
-                    run.cards(),
-                )?;
-                self.state.teams[team].played_runs.push(run);
+    /// Whether the cards left in `hand` after playing may be discarded last and used to close
+    /// the game. Keeping the hand as it is (a `Noop`) is always allowed, after a draw it holds
+    /// at least two cards
+    fn check_closing(
+        state: &BurracoState,
+        hand: &Cards,
+        cards_played: &Cards,
+    ) -> Result<(), BurracoError> {
+        if cards_played.is_empty() {
+            return Ok(());
+        }
+        let mut rest = hand.clone();
+        for card in cards_played.iter() {
+            if let Some(index) = rest.iter().position(|c| c == card) {
+                rest.remove(index);
             }
-            AppendTop(run_idx, cards) => {
-                let all_contained_in_hand =
-                    BurracoGame::cards_in_hand(&cards, self.current_player());
+        }
+        if let [card] = rest.as_slice() {
+            BurracoGame::last_card_penalty(state, card)?;
+        }
+        if !BurracoGame::closes_game(state) {
+            return Ok(());
+        }
+        match rest.as_slice() {
+            [] | [_] if state.close_answer == Some(false) => Err(BurracoError::CloseRefused),
+            [card] if card.is_wildcard() && !state.rules.discard.wildcard_close => {
+                Err(BurracoError::WildcardClose(*card))
+            }
+            _ => Ok(()),
+        }
+    }
 
-                if !all_contained_in_hand {
-                    return Err("Cannot place cards not in hand!".into());
-                }
+    /// Under `Rules::close_needs_burraco`, whether the current team has a burraco once
+    /// `run_change` is made, when playing `cards_played` leaves at most the card to discard
+    fn check_close_burraco(
+        &self,
+        cards_played: &Cards,
+        run_change: &RunChange,
+    ) -> Result<(), BurracoError> {
+        if !self.state.rules.close_needs_burraco
+            || cards_played.is_empty()
+            || cards_played.len() + 1 < self.current_player().hand.len()
+            || !BurracoGame::closes_game(&self.state)
+            || self.current_team().has_burraco()
+        {
+            return Ok(());
+        }
+        match run_change {
+            RunChange::New(run) | RunChange::Replace(_, run) if run.is_burraco() => Ok(()),
+            _ => Err(BurracoError::NoBurracoToClose),
+        }
+    }
 
-                let run = &self.state.teams[team]
-                    .played_runs
-                    .get(run_idx)
-                    .ok_or_else(|| "Non-existing run index".to_string())?;
-                let new_run = run.append(&cards, Append::Top)?;
-                BurracoGame::remove_from_hand(&mut self.state.teams[team].players[player], &cards)?;
-                self.state.teams[team].played_runs[run_idx] = new_run;
-            }
-            AppendBottom(run_idx, cards) => {
-                let all_contained_in_hand =
-                    BurracoGame::cards_in_hand(&cards, self.current_player());
+    /// Check a play action against the current game without applying it
+    pub fn validate(&self, action: &PlayAction) -> Result<Outcome, BurracoError> {
+        if self.phase != GamePhase::Play {
+            return Err(BurracoError::WrongPhase(self.phase));
+        }
 
-                if !all_contained_in_hand {
-                    return Err("Cannot place cards not in hand!".into());
-                }
+        let cards_played = action.cards_played();
 
-                let run = &self.state.teams[team]
-                    .played_runs
-                    .get(run_idx)
-                    .ok_or_else(|| "Non-existing run index".to_string())?;
-                let new_run = run.append(&cards, Append::Bottom)?;
-                BurracoGame::remove_from_hand(&mut self.state.teams[team].players[player], &cards)?;
-                self.state.teams[team].played_runs[run_idx] = new_run;
+        if !BurracoGame::cards_in_hand(&cards_played, self.current_player()) {
+            return Err(BurracoError::CardsNotInHand(cards_played));
+        }
+        if let Some(card) = self.state.must_use {
+            if !cards_played.contains(&card) {
+                return Err(BurracoError::MustUseCard(card));
             }
-            ReplaceWildcard(run_idx, at, card) => {
-                let cards = Cards(vec![card]);
-                let all_contained_in_hand =
-                    BurracoGame::cards_in_hand(&cards, self.current_player());
-
-                if !all_contained_in_hand {
-                    return Err("Cannot place cards not in hand!".into());
-                }
+        }
+        BurracoGame::check_closing(&self.state, &self.current_player().hand, &cards_played)?;
 
-                let run = &self.state.teams[team]
-                    .played_runs
-                    .get(run_idx)
-                    .ok_or_else(|| "Non-existing run index".to_string())?;
-                let new_run = run.replace_wildcard(at, &card)?;
-                BurracoGame::remove_from_hand(&mut self.state.teams[team].players[player], &cards)?;
-                self.state.teams[team].played_runs[run_idx] = new_run;
+        let run_change = match action {
+            Noop => RunChange::None,
+            StartRun(run) if run.rules() == self.state.rules.run => RunChange::New(run.clone()),
+            StartRun(run) => {
+                // built elsewhere, make sure it holds up with the rules of this game
+                let rules = self.state.rules.run;
+                let new_run = match run.run_type() {
+                    RunType::Sequence => Run::build_sequence_run_with(run.cards().clone(), rules)?,
+                    RunType::Group => Run::build_group_run_with(run.cards().clone(), rules)?,
+                };
+                RunChange::New(new_run)
             }
+            AppendTop(run_idx, cards) => RunChange::Replace(
+                *run_idx,
+                self.team_run(*run_idx)?.append(cards, Append::Top)?,
+            ),
+            AppendBottom(run_idx, cards) => RunChange::Replace(
+                *run_idx,
+                self.team_run(*run_idx)?.append(cards, Append::Bottom)?,
+            ),
+            ReplaceWildcard(run_idx, at, card) => RunChange::Replace(
+                *run_idx,
+                self.team_run(*run_idx)?.replace_wildcard(*at, card)?,
+            ),
             MoveCard(run_idx, from, to) => {
                 if from == to {
-                    return Err("No use moving card to same index".into());
+                    return Err(BurracoError::InvalidMove(
+                        "No use moving card to same index".into(),
+                    ));
                 }
-                let run = &self.state.teams[team]
-                    .played_runs
-                    .get(run_idx)
-                    .ok_or_else(|| "Non-existing run index".to_string())?;
-                let new_run = run.move_card(from, to)?;
-                self.state.teams[team].played_runs[run_idx] = new_run;
+                RunChange::Replace(*run_idx, self.team_run(*run_idx)?.move_card(*from, *to)?)
             }
+        };
+        self.check_close_burraco(&cards_played, &run_change)?;
+
+        let score_delta = match &run_change {
+            RunChange::None => 0,
+            RunChange::New(run) => run.score(),
+            RunChange::Replace(run_idx, run) => run.score() - self.team_run(*run_idx)?.score(),
+        };
+        let empties_hand =
+            !cards_played.is_empty() && cards_played.len() == self.current_player().hand.len();
+
+        Ok(Outcome {
+            cards_played,
+            run_change,
+            score_delta,
+            empties_hand,
+        })
+    }
+
+    pub fn play(&mut self, action: PlayAction) -> Result<(), BurracoError> {
+        let outcome = self.validate(&action)?;
+
+        let (team, player) = self.state.player_team_idxs[self.state.player_turn];
+
+        BurracoGame::remove_from_hand(
+            &mut self.state.teams[team].players[player],
+            &outcome.cards_played,
+        )?;
+        self.state.must_use = None;
+        match outcome.run_change {
+            RunChange::None => {}
+            RunChange::New(run) => self.state.teams[team].played_runs.push(run),
+            RunChange::Replace(run_idx, run) => self.state.teams[team].played_runs[run_idx] = run,
+        }
+        if action == Noop {
+            self.phase = GamePhase::Discard;
         }
 
         self.state.teams[team]
@@ -201,24 +461,20 @@ impl BurracoGame {
             .sort_by_key(|r| (r.run_type() != RunType::Sequence, r.cards().len()));
 
         if self.current_player().hand.is_empty() {
-            // TODO prevent action where game ends without team burraco
             if !self.current_team().has_reached_pot {
-                if let Some(non_empty_pot) = [&mut self.state.pot1, &mut self.state.pot2]
-                    .iter_mut()
-                    .find(|p| !p.is_empty())
-                {
+                if let Some(mut pot) = self.state.take_pot() {
                     // "discard and get pot", can only play next turn
-                    self.state.teams[team].players[player]
-                        .hand
-                        .append(&mut non_empty_pot.drain_back(11));
+                    self.state.teams[team].players[player].hand.append(&mut pot);
                     self.state.teams[team].has_reached_pot = true;
                 } else {
-                    // both pots taken, game ends (should really happen in other top else clause?)
+                    // all pots taken, game ends (should really happen in other top else clause?)
+                    self.state.went_out = Some(team);
                     self.phase = GamePhase::Finished(self.winning_team());
                 }
             } else {
                 // team has already reached one pot
                 // TODO: game should end, I think
+                self.state.went_out = Some(team);
                 self.phase = GamePhase::Finished(self.winning_team());
             }
         }
@@ -226,57 +482,107 @@ impl BurracoGame {
         // else continue in draw for next player, if not set to Discard by noop action
 
         // TODO update score?
+        self.debug_check_invariants();
         Ok(())
     }
 
-    pub fn discard(&mut self, action: DiscardAction) -> Result<(), String> {
+    /// The cards the current player may discard
+    pub fn discard_choices(&self) -> Cards {
+        Cards(
+            self.current_player()
+                .hand
+                .iter()
+                .filter(|c| self.validate_discard(&DiscardAction(**c)).is_ok())
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Check a discard against the discard rules, to the penalty it gives
+    pub fn validate_discard(&self, action: &DiscardAction) -> Result<i32, BurracoError> {
         if self.phase != GamePhase::Discard {
-            return Err(format!(
-                "Discard action invalid when phase is: {:?}",
-                self.phase
-            ));
+            return Err(BurracoError::WrongPhase(self.phase));
+        }
+        let card = action.0;
+        let hand = &self.current_player().hand;
+        if !hand.contains(&card) {
+            return Err(BurracoError::CardsNotInHand(Cards(vec![card])));
+        }
+        if hand.len() == 1
+            && card.is_wildcard()
+            && !self.state.rules.discard.wildcard_close
+            && BurracoGame::closes_game(&self.state)
+        {
+            return Err(BurracoError::WildcardClose(card));
         }
+        if hand.len() == 1
+            && self.state.rules.close_needs_burraco
+            && !self.current_team().has_burraco()
+            && BurracoGame::closes_game(&self.state)
+        {
+            return Err(BurracoError::NoBurracoToClose);
+        }
+        let penalty = if hand.len() == 1 {
+            BurracoGame::last_card_penalty(&self.state, &card)?
+        } else {
+            0
+        };
+        match self.discard_restriction(&card) {
+            Err(error)
+                if hand
+                    .iter()
+                    .any(|c| *c != card && self.discard_restriction(c).is_ok()) =>
+            {
+                Err(error)
+            }
+            // nothing else to discard
+            _ => Ok(penalty),
+        }
+    }
+
+    /// The soft restrictions, see `DiscardRules`
+    fn discard_restriction(&self, card: &Card) -> Result<(), BurracoError> {
+        let rules = &self.state.rules.discard;
+        if !rules.discard_picked_single && self.state.picked_single == Some(*card) {
+            return Err(BurracoError::DiscardPicked(*card));
+        }
+        Ok(())
+    }
+
+    pub fn discard(&mut self, action: DiscardAction) -> Result<(), BurracoError> {
+        let penalty = self.validate_discard(&action)?;
         let (team, player) = self.state.player_team_idxs[self.state.player_turn];
-        if let Some(index) = self
+        let index = self
             .current_player()
             .hand
             .iter()
             .position(|c| *c == action.0)
-        {
-            self.state.teams[team].players[player].hand.remove(index);
-            self.state.open_pile.push(action.0);
-        } else {
-            return Err(format!("Cannot discard card not in hand: {}", action.0));
-        }
+            .expect("validated to be in hand");
+        self.state.teams[team].players[player].hand.remove(index);
+        self.state.open_pile.push(action.0);
+        self.state.teams[team].penalties += penalty;
+        self.state.picked_single = None;
+        self.state.close_answer = None;
 
         if self.current_player().hand.is_empty() {
             // TODO code duplication with play check?
             // TODO more sophisticated "reached/used" pot check?
-            // TODO prevent action where game ends without team burraco
             if !self.current_team().has_reached_pot {
-                if !self.state.pot1.is_empty() {
+                if let Some(mut pot) = self.state.take_pot() {
                     // "flying pot", can continue playing
-                    self.state.teams[team].players[player]
-                        .hand
-                        .append(&mut self.state.pot1.drain_back(11));
-                    self.state.teams[team].players[player].hand.sort();
-                    self.state.teams[team].has_reached_pot = true;
-                    self.phase = GamePhase::Draw;
-                } else if !self.state.pot2.is_empty() {
-                    // "flying pot", can continue playing
-                    self.state.teams[team].players[player]
-                        .hand
-                        .append(&mut self.state.pot2.drain_back(11));
+                    self.state.teams[team].players[player].hand.append(&mut pot);
                     self.state.teams[team].players[player].hand.sort();
                     self.state.teams[team].has_reached_pot = true;
                     self.phase = GamePhase::Draw;
                 } else {
-                    // both pots taken
+                    // all pots taken
                     // TODO: should game really end? can other team player have valid moves if not used pot?
+                    self.state.went_out = Some(team);
                     self.phase = GamePhase::Finished(self.winning_team());
                 }
             } else {
                 // team has already reached one pot, game ends
+                self.state.went_out = Some(team);
                 self.phase = GamePhase::Finished(self.winning_team());
             }
         } else {
@@ -292,19 +598,34 @@ impl BurracoGame {
             }
         }
 
+        self.debug_check_invariants();
         Ok(())
     }
 
     pub fn scoreboard(&self) -> Vec<i32> {
         let mut team_scores = Vec::new();
-        for team in &self.state().teams {
+        let rules = &self.state.rules;
+        for (t, team) in self.state().teams.iter().enumerate() {
             // TODO: reached but not used deduction?
             let pot_deduction = if team.has_reached_pot { 0 } else { -100 };
+            let going_out_bonus = if self.state.went_out == Some(t)
+                && (team.has_burraco() || !rules.close_needs_burraco)
+            {
+                rules.going_out_bonus
+            } else {
+                0
+            };
 
             let runs_score: i32 = team.played_runs.iter().map(|r| r.score()).sum();
-            let cards_deduction: i32 = team.players.iter().map(|p| p.hand.value_sum()).sum();
+            let cards_deduction: i32 = team
+                .players
+                .iter()
+                .map(|p| p.hand.value_sum_with(rules.run.scoring))
+                .sum();
 
-            team_scores.push(pot_deduction + runs_score + cards_deduction);
+            team_scores.push(
+                pot_deduction + going_out_bonus + runs_score + cards_deduction - team.penalties,
+            );
         }
 
         team_scores
@@ -322,6 +643,7 @@ impl BurracoGame {
     }
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     Draw(DrawAction),
     Play(PlayAction),
@@ -334,6 +656,46 @@ pub enum DrawAction {
     DrawPile,
 }
 
+impl DrawAction {
+    /// Draw actions the open pile rules allow for the current player
+    pub fn allowed(state: &BurracoState) -> Vec<DrawAction> {
+        if DrawAction::check_draw_open(state).is_ok() {
+            vec![DrawAction::DrawOpen, DrawAction::DrawPile]
+        } else {
+            vec![DrawAction::DrawPile]
+        }
+    }
+
+    fn check_draw_open(state: &BurracoState) -> Result<(), BurracoError> {
+        let top = state.open_pile.last().cloned();
+        match (state.rules.open_pile_draw, top) {
+            (_, None) => Err(BurracoError::InvalidMove("Open pile is empty".into())),
+            (OpenPileDraw::WholePile, Some(_)) | (OpenPileDraw::TopCardOnly, Some(_)) => Ok(()),
+            (OpenPileDraw::WholePileMustUseTop, Some(top)) => {
+                // the plays left after drawing, under the closing rules
+                let (team, player) = state.curr_team_player();
+                let mut drawn = state.clone();
+                let mut open_pile = std::mem::take(&mut drawn.open_pile);
+                drawn.teams[team].players[player]
+                    .hand
+                    .append(&mut open_pile);
+                drawn.must_use = Some(top);
+                if !BurracoGame::at_phase(drawn, GamePhase::Play)
+                    .play_actions(0)
+                    .is_empty()
+                {
+                    Ok(())
+                } else {
+                    Err(BurracoError::InvalidMove(format!(
+                        "No way to play {} from the open pile",
+                        top
+                    )))
+                }
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum PlayAction {
     StartRun(Run),
@@ -349,7 +711,46 @@ pub enum PlayAction {
 }
 
 impl PlayAction {
+    /// Cards leaving the hand of the player
+    pub fn cards_played(&self) -> Cards {
+        match self {
+            StartRun(run) => run.cards().clone(),
+            AppendTop(_, cards) | AppendBottom(_, cards) => cards.clone(),
+            ReplaceWildcard(_, _, card) => Cards(vec![*card]),
+            Noop | MoveCard(_, _, _) => Cards(vec![]),
+        }
+    }
+
+    /// Actions that would place exactly the selected cards, regardless of whether they are valid
+    pub fn candidates_for(
+        rules: &RunRules,
+        team_runs: &[Run],
+        selected: &Cards,
+    ) -> Vec<PlayAction> {
+        if selected.is_empty() {
+            return vec![Noop];
+        }
+
+        let mut actions = Vec::new();
+        for run in Run::arrangements_with(selected, *rules) {
+            actions.push(StartRun(run));
+        }
+        for (i, run) in team_runs.iter().enumerate() {
+            actions.push(AppendTop(i, selected.clone()));
+            actions.push(AppendBottom(i, selected.clone()));
+            if selected.len() == 1 {
+                for (k, card) in run.cards().iter().enumerate() {
+                    if card.1 == Joker || card.1 == Two {
+                        actions.push(ReplaceWildcard(i, k, selected[0]));
+                    }
+                }
+            }
+        }
+        actions
+    }
+
     pub fn enumerate(
+        rules: &RunRules,
         team_runs: &[Run],
         player_hand: &Cards,
         moves_allowed: usize,
@@ -387,7 +788,8 @@ impl PlayAction {
                     eprintln!("p-start");
 
                     let card3 = player_hand[k];
-                    let maybe_run = Run::build_sequence_run(Cards(vec![card1, card2, card3]));
+                    let maybe_run =
+                        Run::build_sequence_run_with(Cards(vec![card1, card2, card3]), *rules);
                     if let Ok(run) = maybe_run {
                         #[cfg(feature = "enumerate_profiling")]
                         eprintln!("p-start-ok");
@@ -409,7 +811,8 @@ impl PlayAction {
 
                     let card3 = player_hand[k];
 
-                    let maybe_run = Run::build_group_run(Cards(vec![card1, card2, card3]));
+                    let maybe_run =
+                        Run::build_group_run_with(Cards(vec![card1, card2, card3]), *rules);
                     if let Ok(run) = maybe_run {
                         #[cfg(feature = "enumerate_profiling")]
                         eprintln!("p-start-g-ok");
@@ -509,20 +912,53 @@ impl PlayAction {
     }
 }
 
+/// How a play action changes the team runs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunChange {
+    None,
+    New(Run),
+    /// run_idx, run after the action
+    Replace(usize, Run),
+}
+
+/// What a valid play action would do, see `BurracoGame::validate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    /// cards leaving the hand of the current player
+    pub cards_played: Cards,
+    pub run_change: RunChange,
+    /// change in the team's run score
+    pub score_delta: i32,
+    pub empties_hand: bool,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct DiscardAction(pub Card);
 
+impl DiscardAction {
+    /// Whether the next opponent could attach the card to one of their runs
+    pub fn feeds_next_opponent(&self, state: &BurracoState) -> bool {
+        let runs = &state.teams[state.next_opponent_team()].played_runs;
+        let actions_now = PlayAction::enumerate(&state.rules.run, runs, &Cards(vec![]), 0);
+        let actions_with_card =
+            PlayAction::enumerate(&state.rules.run, runs, &Cards(vec![self.0]), 0);
+        actions_with_card.len() > actions_now.len()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::agent::BurracoAgent;
+    use crate::model::Suit::*;
+    use crate::rules::DiscardRules;
 
     // ♣ ♦ ♥ ♠
 
     #[test]
     fn test_start_run_action() -> Result<(), String> {
         let hand = Cards::of("JK, ♣2, ♣5, ♣7, ♣9, ♣K, ♦6, ♦8, ♦9, ♥10, ♠6, ♠K")?;
-        let actions = PlayAction::enumerate(&[], &hand, 0);
+        let actions = PlayAction::enumerate(&RunRules::default(), &[], &hand, 0);
         for (action, _d_score) in &actions {
             println!("{}", action);
         }
@@ -541,8 +977,8 @@ mod tests {
             "♣2,♦8,♦9",
             "♦8,♦9,JK",
             "♦8,♦9,♣2", // TODO group runs
-                              // TODO: only accept in certain order to prevent state explosion?
-                              // "♦6,♠6,♣2"
+                        // TODO: only accept in certain order to prevent state explosion?
+                        // "♦6,♠6,♣2"
         ];
 
         let should_enumerate_runs: Vec<Result<PlayAction, String>> = should_enumerate_runs_s
@@ -569,6 +1005,7 @@ mod tests {
     fn test_move_action() -> Result<(), String> {
         let hand = Cards::of("♣5")?;
         let actions = PlayAction::enumerate(
+            &RunRules::default(),
             &[Run::build_sequence_run(Cards::of("JK,♥3,♥4")?)?],
             &hand,
             1,
@@ -589,7 +1026,7 @@ mod tests {
 
     #[test]
     fn test_advance_turn() -> Result<(), String> {
-        let mut state = BurracoState::init_with(2, 2);
+        let mut state = BurracoState::init_with(2, 2)?;
         // for deterministic test
         state.first_player = 0;
         state.player_turn = 0;
@@ -637,6 +1074,401 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_three_player_pots() -> Result<(), String> {
+        let mut state = BurracoState::init_seeded(3, 1, Some(0))?;
+        state.first_player = 0;
+        state.player_turn = 0;
+        let runs = ["♣4, ♣5, ♣6", "♦4, ♦5, ♦6", "♥4, ♥5, ♥6"];
+        for (team, run) in runs.iter().enumerate() {
+            state.teams[team].players[0].hand = Cards::of(run)?;
+        }
+        let mut game = BurracoGame::from(state);
+
+        for (team, run) in runs.iter().enumerate() {
+            assert_eq!(team, game.state.curr_team());
+            game.draw(DrawAction::DrawPile)?;
+            game.play(StartRun(Run::build_sequence_run(Cards::of(run)?)?))?;
+            game.play(PlayAction::Noop)?;
+            let last = game.current_player().hand[0];
+            game.discard(DiscardAction(last))?;
+            // each player gets a pot of their own, the last one the biggest
+            assert_eq!(GamePhase::Draw, game.phase);
+            assert_eq!(
+                game.state.rules.table.pot_sizes()[team],
+                game.state.teams[team].players[0].hand.len()
+            );
+            assert!(game.state.teams[team].has_reached_pot);
+            assert_eq!(
+                2 - team,
+                game.state.pots.iter().filter(|p| !p.is_empty()).count()
+            );
+        }
+        assert_eq!(None, game.state.take_pot());
+
+        Ok(())
+    }
+
+    fn state_with_open_pile_draw(open_pile_draw: OpenPileDraw) -> BurracoState {
+        use crate::rules::Rules;
+
+        let rules = Rules {
+            open_pile_draw,
+            ..Rules::default()
+        };
+        let mut state = BurracoState::init_with_rules(rules, Some(0));
+        state.player_turn = 0;
+        state
+    }
+
+    #[test]
+    fn test_draw_whole_pile() -> Result<(), String> {
+        let mut state = state_with_open_pile_draw(OpenPileDraw::WholePile);
+        state.teams[0].players[0].hand = Cards::of("♠4, ♠5")?;
+        state.open_pile = Cards::of("♣5, ♦9")?;
+        let mut game = BurracoGame::from(state);
+        game.draw(DrawAction::DrawOpen)?;
+        assert_eq!(4, game.current_player().hand.len());
+        assert!(game.state.open_pile.is_empty());
+
+        let mut state = state_with_open_pile_draw(OpenPileDraw::WholePile);
+        state.open_pile = Cards(vec![]);
+        let mut game = BurracoGame::from(state);
+        assert_eq!(
+            vec![DrawAction::DrawPile],
+            DrawAction::allowed(game.state())
+        );
+        assert!(matches!(
+            game.draw(DrawAction::DrawOpen),
+            Err(BurracoError::InvalidMove(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_top_card_only() -> Result<(), String> {
+        let mut state = state_with_open_pile_draw(OpenPileDraw::TopCardOnly);
+        state.teams[0].players[0].hand = Cards::of("♠4, ♠5")?;
+        state.open_pile = Cards::of("♣5, ♦9")?;
+        let mut game = BurracoGame::from(state);
+        game.draw(DrawAction::DrawOpen)?;
+        assert_eq!(Cards::of("♦9, ♠4, ♠5")?, game.current_player().hand);
+        assert_eq!(Cards::of("♣5")?, game.state.open_pile);
+
+        let mut state = state_with_open_pile_draw(OpenPileDraw::TopCardOnly);
+        state.open_pile = Cards(vec![]);
+        let mut game = BurracoGame::from(state);
+        assert_eq!(
+            vec![DrawAction::DrawPile],
+            DrawAction::allowed(game.state())
+        );
+        assert!(game.draw(DrawAction::DrawOpen).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_whole_pile_must_use_top() -> Result<(), String> {
+        let mut state = state_with_open_pile_draw(OpenPileDraw::WholePileMustUseTop);
+        state.teams[0].players[0].hand = Cards::of("♠4, ♠5, ♥9, ♥J")?;
+        state.open_pile = Cards::of("♣K, ♠6")?;
+        let mut game = BurracoGame::from(state);
+        game.draw(DrawAction::DrawOpen)?;
+        assert_eq!(6, game.current_player().hand.len());
+        assert_eq!(Some(Card(Spades, Numerical(6))), game.state.must_use);
+
+        assert_eq!(
+            Err(BurracoError::MustUseCard(Card(Spades, Numerical(6)))),
+            game.play(Noop)
+        );
+        let actions = game.play_actions(1);
+        assert!(!actions.is_empty());
+        assert!(actions
+            .iter()
+            .all(|(a, _)| a.cards_played().contains(&Card(Spades, Numerical(6)))));
+
+        game.play(StartRun(Run::build_sequence_run(Cards::of("♠4, ♠5, ♠6")?)?))?;
+        assert_eq!(None, game.state.must_use);
+        game.play(Noop)?;
+        assert_eq!(GamePhase::Discard, game.phase());
+
+        let mut state = state_with_open_pile_draw(OpenPileDraw::WholePileMustUseTop);
+        state.teams[0].players[0].hand = Cards::of("♠4, ♠5, ♥9, ♥J")?;
+        state.open_pile = Cards::of("♠6, ♦K")?;
+        let mut game = BurracoGame::from(state);
+        assert_eq!(
+            vec![DrawAction::DrawPile],
+            DrawAction::allowed(game.state())
+        );
+        assert!(matches!(
+            game.draw(DrawAction::DrawOpen),
+            Err(BurracoError::InvalidMove(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_brazilian_going_out_bonus() -> Result<(), String> {
+        use crate::rules::Rules;
+
+        let mut state = BurracoState::init_with_rules(Rules::brazilian(), Some(0));
+        state.player_turn = 0;
+        for (team, pot) in state.teams.iter_mut().zip(state.pots.iter_mut()) {
+            team.has_reached_pot = true;
+            *pot = Cards(vec![]);
+            for player in team.players.iter_mut() {
+                player.hand = Cards::of("♥3")?;
+            }
+        }
+        state.teams[0].players[0].hand = Cards::of("♠4, ♠5, ♠6")?;
+        state.draw_pile = Cards::of("♣5, ♦K")?;
+        let run = Run::build_sequence_run_with(Cards::of("♠4, ♠5, ♠6")?, RunRules::brazilian())?;
+
+        // no canastra, the run would leave only the card to discard
+        let mut game = BurracoGame::from(state.clone());
+        game.draw(DrawAction::DrawPile)?;
+        assert!(matches!(
+            game.validate(&StartRun(run.clone())),
+            Err(BurracoError::NoBurracoToClose)
+        ));
+        assert_eq!(vec![(Noop, 0)], game.play_actions(0));
+        game.play(Noop)?;
+        let mut lone_card = BurracoGame::at_phase(game.state().clone(), GamePhase::Discard);
+        lone_card.state.teams[0].players[0].hand = Cards::of("♦K")?;
+        assert_eq!(
+            Err(BurracoError::NoBurracoToClose),
+            lone_card.validate_discard(&DiscardAction(Card(Diamonds, King)))
+        );
+
+        state.teams[0].played_runs = vec![Run::build_sequence_run_with(
+            Cards::of("♥4, ♥5, ♥6, ♥7, ♥8, ♥9, ♥10")?,
+            RunRules::brazilian(),
+        )?];
+        let mut game = BurracoGame::from(state);
+        game.draw(DrawAction::DrawPile)?;
+        game.play(StartRun(run))?;
+        game.play(Noop)?;
+        assert_eq!(Cards::of("♦K")?, game.current_player().hand);
+        game.discard(DiscardAction(Card(Diamonds, King)))?;
+
+        assert_eq!(GamePhase::Finished(0), game.phase());
+        assert_eq!(Some(0), game.state.went_out);
+        let scores = game.scoreboard();
+        game.state.went_out = None;
+        assert_eq!(vec![scores[0] - 100, scores[1]], game.scoreboard());
+        Ok(())
+    }
+
+    fn state_with_discard_rules(discard: DiscardRules) -> BurracoState {
+        use crate::rules::Rules;
+
+        let rules = Rules {
+            discard,
+            ..Rules::default()
+        };
+        let mut state = BurracoState::init_with_rules(rules, Some(0));
+        state.player_turn = 0;
+        state
+    }
+
+    #[test]
+    fn test_wildcard_close() -> Result<(), String> {
+        use crate::rules::Rules;
+        use crate::scenario::ScenarioBuilder;
+
+        let rules = Rules {
+            discard: DiscardRules::federation(),
+            ..Rules::default()
+        };
+        let mut game = ScenarioBuilder::new(rules)
+            .hand(0, "♠4, ♠5, ♠6, JK")
+            .reached_pot(0)
+            .phase(GamePhase::Play)
+            .build()?;
+        let run = Run::build_sequence_run(Cards::of("♠4, ♠5, ♠6")?)?;
+        assert_eq!(
+            Err(BurracoError::WildcardClose(Card(Jokers, Joker))),
+            game.play(StartRun(run.clone()))
+        );
+        assert!(!game.play_actions(0).contains(&(StartRun(run), 15)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_lone_wildcard_left() -> Result<(), String> {
+        use crate::rules::Rules;
+        use crate::scenario::ScenarioBuilder;
+
+        let rules = Rules {
+            discard: DiscardRules::federation(),
+            ..Rules::default()
+        };
+        let mut game = ScenarioBuilder::new(rules)
+            .hand(0, "♠4, ♠5, JK")
+            .reached_pot(0)
+            .open_pile("")
+            .draw_pile("♠6")
+            .build()?;
+        assert_eq!(
+            vec![DrawAction::DrawPile],
+            DrawAction::allowed(game.state())
+        );
+        assert!(game.draw(DrawAction::DrawOpen).is_err());
+        game.draw(DrawAction::DrawPile)?;
+
+        // the plays leaving the Joker alone are left out, the ones using it are not
+        let run = Run::build_sequence_run(Cards::of("♠4, ♠5, ♠6")?)?;
+        assert!(matches!(
+            game.validate(&StartRun(run.clone())),
+            Err(BurracoError::WildcardClose(Card(Jokers, Joker)))
+        ));
+        let actions = game.play_actions(0);
+        assert!(!actions.contains(&(StartRun(run), 15)));
+        assert!(actions
+            .iter()
+            .any(|(a, _d_score)| a.cards_played().contains(&Card(Jokers, Joker))));
+
+        // keeping the hand leaves every card to discard
+        game.play(Noop)?;
+        assert_eq!(Cards::of("JK, ♠4, ♠5, ♠6")?, game.discard_choices());
+        Ok(())
+    }
+
+    #[test]
+    fn test_discard_picked_single() -> Result<(), String> {
+        let mut state = state_with_discard_rules(DiscardRules::federation());
+        state.teams[0].players[0].hand = Cards::of("♠4, ♥K")?;
+        state.open_pile = Cards::of("♦9")?;
+        let mut game = BurracoGame::from(state);
+        game.draw(DrawAction::DrawOpen)?;
+        game.play(Noop)?;
+        assert_eq!(
+            Err(BurracoError::DiscardPicked(Card(Diamonds, Numerical(9)))),
+            game.discard(DiscardAction(Card(Diamonds, Numerical(9))))
+        );
+        assert_eq!(Cards::of("♥K, ♠4")?, game.discard_choices());
+
+        // nothing else to discard
+        game.state.teams[0].players[0].hand = Cards::of("♦9")?;
+        assert_eq!(Cards::of("♦9")?, game.discard_choices());
+        Ok(())
+    }
+
+    #[test]
+    fn test_feeding_discard() -> Result<(), String> {
+        let opponent_run = Run::build_sequence_run(Cards::of("♥5, ♥6, ♥7")?)?;
+        let mut state = state_with_discard_rules(DiscardRules::federation());
+        state.teams[1].played_runs = vec![opponent_run.clone()];
+        state.teams[0].players[0].hand = Cards::of("♥8, ♣K")?;
+        let mut game = BurracoGame::from(state.clone());
+        game.phase = GamePhase::Discard;
+        // any card but the last one may feed the opponent
+        assert_eq!(
+            Ok(0),
+            game.validate_discard(&DiscardAction(Card(Hearts, Numerical(8))))
+        );
+        assert_eq!(Cards::of("♥8, ♣K")?, game.discard_choices());
+
+        // the plays leaving only a card feeding the opponent are not allowed
+        state.teams[0].players[0].hand = Cards::of("♣J, ♣Q, ♣K, ♥8")?;
+        let mut game = BurracoGame::from(state);
+        game.phase = GamePhase::Play;
+        let run = Run::build_sequence_run(Cards::of("♣J, ♣Q, ♣K")?)?;
+        assert!(matches!(
+            game.validate(&StartRun(run.clone())),
+            Err(BurracoError::FeedsOpponent(Card(Hearts, Numerical(8))))
+        ));
+        assert!(!game
+            .play_actions(0)
+            .iter()
+            .any(|(a, _d_score)| *a == StartRun(run.clone())));
+
+        let mut state = state_with_discard_rules(DiscardRules {
+            feeding: FeedingDiscard::Penalty(20),
+            ..DiscardRules::default()
+        });
+        state.teams[1].played_runs = vec![opponent_run];
+        state.teams[0].players[0].hand = Cards::of("♣J, ♣Q, ♣K, ♥8")?;
+        let mut game = BurracoGame::from(state);
+        game.phase = GamePhase::Play;
+        game.play(StartRun(run))?;
+        game.play(Noop)?;
+        game.discard(DiscardAction(Card(Hearts, Numerical(8))))?;
+        assert_eq!(20, game.state.teams[0].penalties);
+        Ok(())
+    }
+
+    #[test]
+    fn test_close_query() -> Result<(), String> {
+        use crate::rules::Rules;
+        use crate::scenario::ScenarioBuilder;
+
+        let rules = Rules {
+            close_query: true,
+            ..Rules::default()
+        };
+        let mut game = ScenarioBuilder::new(rules)
+            .hand(0, "♠4, ♠5, ♠6, ♥9")
+            .reached_pot(0)
+            .phase(GamePhase::Play)
+            .build()?;
+        let run = StartRun(Run::build_sequence_run(Cards::of("♠4, ♠5, ♠6")?)?);
+
+        let mut game_yes = game.clone();
+        assert_eq!(Ok(2), game_yes.close_query_partner());
+        assert_eq!(
+            Ok(true),
+            game_yes.ask_close(|partner, view| partner == 2 && view.player == 2)
+        );
+        game_yes.play(run.clone())?;
+
+        assert_eq!(Ok(false), game.ask_close(|_, _| false));
+        assert!(game.ask_close(|_, _| false).is_err());
+        assert_eq!(Err(BurracoError::CloseRefused), game.play(run.clone()));
+        assert!(game.play_actions(0).iter().all(|(a, _)| *a != run));
+        game.play(Noop)?;
+        game.discard(DiscardAction(Card(Hearts, Numerical(9))))?;
+        assert_eq!(None, game.state.close_answer);
+
+        let mut game = BurracoGame::from(BurracoState::init_seeded(2, 2, Some(0))?);
+        game.phase = GamePhase::Play;
+        assert!(game.close_query_partner().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_invariants() -> Result<(), String> {
+        let state = BurracoState::init_seeded(2, 2, Some(0))?;
+        let mut game = BurracoGame::from(state.clone());
+        game.draw(DrawAction::DrawPile)?;
+        assert_eq!(Ok(()), game.check_invariants());
+
+        let mut lost_card = BurracoGame::from(state.clone());
+        lost_card.state.draw_pile.pop();
+        assert!(lost_card.check_invariants().is_err());
+
+        let mut with_run = BurracoGame::from(state.clone());
+        let run = Run::build_sequence_run(Cards::of("♠4, ♠5, ♠6")?)?;
+        with_run.state.teams[0].played_runs = vec![run];
+        with_run.original_cards = with_run.state.all_cards().deck_counts();
+        assert_eq!(Ok(()), with_run.check_invariants());
+
+        let mut no_pot = BurracoGame::from(state.clone());
+        no_pot.state.teams[1].has_reached_pot = true;
+        assert!(no_pot.check_invariants().is_err());
+
+        let mut empty_hand = BurracoGame::from(state.clone());
+        empty_hand.state.teams[0].players[1].hand = Cards(vec![]);
+        empty_hand.original_cards = empty_hand.state.all_cards().deck_counts();
+        assert!(empty_hand.check_invariants().is_err());
+        empty_hand.phase = GamePhase::Finished(0);
+        assert_eq!(Ok(()), empty_hand.check_invariants());
+
+        let mut bad_seats = BurracoGame::from(state);
+        bad_seats.state.player_team_idxs[0] = (0, 1);
+        assert!(bad_seats.check_invariants().is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_move_wildcard_to_last() -> Result<(), String> {
         let run = Cards::of("JK, ♠3, ♠4")?;
@@ -644,7 +1476,7 @@ mod tests {
 
         let hand = Cards::of("♥3")?;
 
-        let actions = PlayAction::enumerate(&[run], &hand, 1);
+        let actions = PlayAction::enumerate(&RunRules::default(), &[run], &hand, 1);
         for (action, _d_score) in &actions {
             println!("{}", action);
         }
@@ -658,4 +1490,93 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_validate_append() -> Result<(), String> {
+        let mut state = BurracoState::init_seeded(2, 2, Some(0))?;
+        state.teams[0].players[0].hand = Cards::of("♠6, ♠9, ♥K")?;
+        state.teams[0]
+            .played_runs
+            .push(Run::build_sequence_run(Cards::of("♠3,♠4,♠5")?)?);
+        state.player_turn = 0;
+
+        let mut game = BurracoGame::from(state);
+        assert_eq!(
+            Err(BurracoError::WrongPhase(GamePhase::Draw)),
+            game.validate(&PlayAction::Noop)
+        );
+        game.phase = GamePhase::Play;
+
+        let outcome = game.validate(&AppendTop(0, Cards::of("♠6")?))?;
+        assert_eq!(Cards::of("♠6")?, outcome.cards_played);
+        assert_eq!(5, outcome.score_delta);
+        assert!(!outcome.empties_hand);
+
+        assert!(matches!(
+            game.validate(&AppendTop(0, Cards::of("♠9")?)),
+            Err(BurracoError::InvalidRun(_))
+        ));
+        assert!(matches!(
+            game.validate(&AppendTop(0, Cards::of("♠7")?)),
+            Err(BurracoError::CardsNotInHand(_))
+        ));
+        assert_eq!(
+            Err(BurracoError::InvalidRunIndex(1)),
+            game.validate(&AppendBottom(1, Cards::of("♠6")?))
+        );
+
+        // validation does not change the game
+        assert_eq!(3, game.current_player().hand.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_start_run_with_game_rules() -> Result<(), String> {
+        use crate::rules::AcePolicy;
+        use crate::rules::Rules;
+
+        let rules = Rules {
+            run: RunRules {
+                ace_policy: AcePolicy::LowOnly,
+                ..RunRules::default()
+            },
+            ..Rules::default()
+        };
+        let mut state = BurracoState::init_with_rules(rules, Some(0));
+        state.player_turn = 0;
+        state.teams[0].players[0].hand = Cards::of("♠Q, ♠K, ♠A, ♠2, ♠3")?;
+        let mut game = BurracoGame::from(state);
+        game.phase = GamePhase::Play;
+
+        let ace_high = Run::build_sequence_run(Cards::of("♠Q,♠K,♠A")?)?;
+        assert!(matches!(
+            game.validate(&StartRun(ace_high)),
+            Err(BurracoError::InvalidRun(_))
+        ));
+        let ace_low = Run::build_sequence_run(Cards::of("♠A,♠2,♠3")?)?;
+        game.play(StartRun(ace_low))?;
+        assert_eq!(
+            AcePolicy::LowOnly,
+            game.current_team().played_runs[0].rules().ace_policy
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_candidates_for_selection() -> Result<(), String> {
+        let runs = [Run::build_sequence_run(Cards::of("♠3,JK,♠5")?)?];
+        let candidates = PlayAction::candidates_for(&RunRules::default(), &runs, &Cards::of("♠4")?);
+        assert!(candidates.contains(&ReplaceWildcard(0, 1, Card(Spades, Numerical(4)))));
+        assert!(candidates.contains(&AppendTop(0, Cards::of("♠4")?)));
+
+        let candidates =
+            PlayAction::candidates_for(&RunRules::default(), &runs, &Cards::of("♥7,♥6,JK")?);
+        assert!(candidates.contains(&StartRun(Run::build_sequence_run(Cards::of("JK,♥6,♥7")?)?)));
+        assert!(candidates.contains(&StartRun(Run::build_sequence_run(Cards::of("♥6,♥7,JK")?)?)));
+
+        let candidates =
+            PlayAction::candidates_for(&RunRules::default(), &runs, &Cards::of("♥7,♦7,♣7")?);
+        assert!(candidates.contains(&StartRun(Run::build_group_run(Cards::of("♥7,♦7,♣7")?)?)));
+        Ok(())
+    }
 }
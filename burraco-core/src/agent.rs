@@ -1,39 +1,170 @@
+use crate::actions::Action;
+use crate::actions::BurracoGame;
 use crate::actions::DiscardAction;
 use crate::actions::DrawAction;
+use crate::actions::GamePhase;
 use crate::actions::PlayAction;
+use crate::actions::RunChange;
+use crate::budget::Budget;
 use crate::cli_display::print_play_actions;
+use crate::difficulty::Difficulty;
+use crate::evaluate::closing_risk;
+use crate::evaluate::could_run_with;
+use crate::evaluate::estimate_discard;
+use crate::evaluate::estimate_draw;
+use crate::evaluate::estimate_draw_within;
+use crate::evaluate::DiscardEstimate;
+use crate::evaluate::DrawEstimate;
+use crate::features::FeatureExtractor;
+use crate::hint::hints;
+use crate::hint::HintConfig;
+use crate::model::Append;
 use crate::model::BurracoState;
+use crate::model::Card;
 use crate::model::Cards;
+use crate::model::PlayerView;
 use crate::model::Rank;
 use crate::model::RunType;
+use crate::policy::LinearPolicy;
+use crate::weights::Weights;
+use std::cmp::Reverse;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Debug)]
 pub enum AgentType {
     Dumb,
     Smart,
     Random,
     SeededRandom(u64),
     Max,
+    /// Smart, but draws by `estimate_draw` and discards by `estimate_discard`
+    Lookahead,
+    /// Values plays and discards by the weights, e.g. loaded with `Weights::load`
+    Weighted(Weights),
+    /// Plays by human strategy, see `ExpertAgent`
+    Expert,
+    /// Scores the candidate actions by their features, e.g. loaded with `LinearPolicy::load`
+    Linear(Arc<LinearPolicy>),
+    /// Plays at the difficulty, see `Difficulty`
+    Level(Difficulty),
+    /// As `Level`, with the mistakes seeded
+    SeededLevel(Difficulty, u64),
     ManualCli,
     /// Assume external code drives action selection on BurracoGame
     Manual,
 }
 
+impl FromStr for AgentType {
+    type Err = String;
+
+    /// An agent by name, one of dumb, smart, max, lookahead and expert, a difficulty as in
+    /// level-easy, its mistakes differing from game to game, or else a file of a `LinearPolicy`
+    /// when it ends with .policy and of `Weights` when it exists or has another extension
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if let Some(level) = name.strip_prefix("level-") {
+            return Ok(AgentType::Level(level.parse()?));
+        }
+        Ok(match name {
+            "dumb" => AgentType::Dumb,
+            "smart" => AgentType::Smart,
+            "max" => AgentType::Max,
+            "lookahead" => AgentType::Lookahead,
+            "expert" => AgentType::Expert,
+            path if path.ends_with(".policy") => {
+                AgentType::Linear(Arc::new(LinearPolicy::load(path)?))
+            }
+            path if Path::new(path).exists() || Path::new(path).extension().is_some() => {
+                AgentType::Weighted(Weights::load(path)?)
+            }
+            _ => return Err(format!("Unknown agent {}", name)),
+        })
+    }
+}
+
 pub fn create_agent(agent_type: AgentType) -> Box<dyn BurracoAgent> {
     let agent: Box<dyn BurracoAgent> = match agent_type {
         AgentType::Dumb => Box::new(DumbAgent {}),
         AgentType::Smart => Box::new(SmartAgent {}),
         AgentType::Max => Box::new(MaxAgent {}),
+        AgentType::Lookahead => Box::new(LookaheadAgent {}),
+        AgentType::Weighted(weights) => Box::new(WeightedAgent { weights }),
+        AgentType::Expert => Box::new(ExpertAgent::default()),
+        AgentType::Linear(policy) => Box::new(LinearPolicyAgent { policy }),
         AgentType::Random => Box::new(random_agent_thread_rng()),
         AgentType::SeededRandom(seed) => Box::new(RandomAgent {
             rng: StdRng::seed_from_u64(seed),
         }),
+        AgentType::Level(level) => level_agent(level, thread_rng()),
+        AgentType::SeededLevel(level, seed) => level_agent(level, StdRng::seed_from_u64(seed)),
         AgentType::ManualCli => Box::new(ManualCliAgent {}),
         _ => unimplemented!(),
     };
     agent
 }
 
+fn level_agent<R: Rng + 'static>(level: Difficulty, rng: R) -> Box<dyn BurracoAgent> {
+    let agent = create_agent(level.agent_type());
+    if level.mistake_rate() > 0.0 {
+        Box::new(MistakenAgent {
+            agent,
+            mistake_rate: level.mistake_rate(),
+            rng,
+        })
+    } else {
+        agent
+    }
+}
+
+/// Why an agent decides as it does, see `BurracoAgent::explain_play`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Explanation {
+    pub reason: String,
+    /// the actions the agent weighed, best first, so the first is the one it makes
+    pub ranked: Vec<Alternative>,
+}
+
+impl Explanation {
+    /// The action the agent makes
+    pub fn best(&self) -> Option<&Action> {
+        self.ranked.first().map(|a| &a.action)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternative {
+    pub action: Action,
+    /// by the measure of the agent, which the reason tells
+    pub score: f64,
+    pub note: String,
+}
+
+impl Alternative {
+    pub fn new(action: Action, score: f64, note: impl Into<String>) -> Alternative {
+        Alternative {
+            action,
+            score,
+            note: note.into(),
+        }
+    }
+}
+
+/// An action an agent chose, with why if it can tell, see `BurracoAgent::select_play_action_within`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Choice<T> {
+    pub action: T,
+    pub explanation: Option<Explanation>,
+}
+
+/// Draws from the open pile when the rules allow it, otherwise from the draw pile
+fn draw_open_if_allowed(state: &BurracoState) -> DrawAction {
+    DrawAction::allowed(state)
+        .into_iter()
+        .next()
+        .expect("drawing from the draw pile is always allowed")
+}
+
 pub trait BurracoAgent {
     fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction;
     fn select_play_action(
@@ -41,7 +172,84 @@ pub trait BurracoAgent {
         actions: Vec<(PlayAction, i32)>,
         state: &BurracoState,
     ) -> PlayAction;
+    /// `hand` holds the cards the discard rules allow, see `BurracoGame::discard_choices`
     fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction;
+    /// As `select_draw_action`, but None once the budget is exhausted, see `budget::decide_draw`,
+    /// and explained along the way when the agent can tell why.
+    /// Agents that search spend the budget as they go, the others ignore it.
+    fn select_draw_action_within(
+        &mut self,
+        state: &BurracoState,
+        _budget: &mut Budget,
+    ) -> Option<Choice<DrawAction>> {
+        let explanation = self.explain_draw(state);
+        let action = match explanation.as_ref().and_then(Explanation::best) {
+            Some(Action::Draw(action)) => *action,
+            _ => self.select_draw_action(state),
+        };
+        Some(Choice {
+            action,
+            explanation,
+        })
+    }
+    fn select_play_action_within(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+        _budget: &mut Budget,
+    ) -> Option<Choice<PlayAction>> {
+        let explanation = self.explain_play(&actions, state);
+        let action = match explanation.as_ref().and_then(Explanation::best) {
+            Some(Action::Play(action)) => action.clone(),
+            _ => self.select_play_action(actions, state),
+        };
+        Some(Choice {
+            action,
+            explanation,
+        })
+    }
+    fn select_discard_action_within(
+        &mut self,
+        hand: &Cards,
+        state: &BurracoState,
+        _budget: &mut Budget,
+    ) -> Option<Choice<DiscardAction>> {
+        let explanation = self.explain_discard(hand, state);
+        let action = match explanation.as_ref().and_then(Explanation::best) {
+            Some(Action::Discard(action)) => *action,
+            _ => self.select_discard_action(hand, state),
+        };
+        Some(Choice {
+            action,
+            explanation,
+        })
+    }
+    /// Why the agent makes the draw it does, None for agents that cannot tell. The action
+    /// ranked first is the one `select_draw_action` makes.
+    fn explain_draw(&mut self, _state: &BurracoState) -> Option<Explanation> {
+        None
+    }
+    /// As `explain_draw`, for `select_play_action`
+    fn explain_play(
+        &mut self,
+        _actions: &[(PlayAction, i32)],
+        _state: &BurracoState,
+    ) -> Option<Explanation> {
+        None
+    }
+    /// As `explain_draw`, for `select_discard_action`
+    fn explain_discard(&mut self, _hand: &Cards, _state: &BurracoState) -> Option<Explanation> {
+        None
+    }
+    /// Whether to ask the partner "may I close?" before playing, only queried when the rules
+    /// allow asking, see `tournament::offer_close_query`
+    fn wants_to_ask_close(&mut self, _view: &PlayerView) -> bool {
+        false
+    }
+    /// Answer a partner asking "may I close?", see `BurracoGame::ask_close`
+    fn answer_close_query(&mut self, _view: &PlayerView) -> bool {
+        true
+    }
     fn display(&self) -> String;
 }
 
@@ -49,10 +257,10 @@ pub struct DumbAgent {}
 
 impl BurracoAgent for DumbAgent {
     fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
-        if state.round % 2 == 0 {
+        if state.round.is_multiple_of(2) {
             DrawAction::DrawPile
         } else {
-            DrawAction::DrawOpen
+            draw_open_if_allowed(state)
         }
     }
 
@@ -97,56 +305,116 @@ impl SmartAgent {
             PlayAction::MoveCard(_, _, _) => 999,
         }
     }
-}
 
-impl BurracoAgent for SmartAgent {
-    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+    /// By preference, then the smallest score change first
+    fn ranked_plays(actions: &[(PlayAction, i32)]) -> Vec<(PlayAction, i32)> {
+        let mut actions = actions.to_vec();
+        actions.sort_by_key(|(a, d_score)| (SmartAgent::play_action_preference(a), *d_score));
+        actions
+    }
+
+    fn draw_with_reason(state: &BurracoState) -> (DrawAction, &'static str) {
         let (team, team_player) = state.curr_team_player();
         let mut hand = state.teams[team].players[team_player].hand.clone();
-        let actions_now = PlayAction::enumerate(&state.teams[team].played_runs, &hand, 0);
-        let open_gives_more_actions = state.open_pile.iter().any(|c| {
+        let actions_now =
+            PlayAction::enumerate(&state.rules.run, &state.teams[team].played_runs, &hand, 0);
+        let open_gives_more_actions = state.open_pile_draw().iter().any(|c| {
             hand.push(*c);
-            let actions_after = PlayAction::enumerate(&state.teams[team].played_runs, &hand, 0);
+            let actions_after =
+                PlayAction::enumerate(&state.rules.run, &state.teams[team].played_runs, &hand, 0);
             hand.pop();
             actions_after.len() > actions_now.len()
         });
 
-        if open_gives_more_actions {
-            DrawAction::DrawOpen
-        } else if state.round % 2 == 0 {
-            DrawAction::DrawPile
+        let reason = if open_gives_more_actions {
+            "the open pile makes more plays possible"
+        } else if state.round.is_multiple_of(2) {
+            return (DrawAction::DrawPile, "draws from the pile on even rounds");
         } else {
-            DrawAction::DrawOpen
+            "takes the open pile on odd rounds"
+        };
+        match draw_open_if_allowed(state) {
+            DrawAction::DrawPile => (DrawAction::DrawPile, "the open pile may not be taken"),
+            action => (action, reason),
         }
     }
 
+    fn discard_with_reason(hand: &Cards, state: &BurracoState) -> (DiscardAction, &'static str) {
+        // discard first card that does not immediately give oppenent a benefit
+        for card in hand.iter() {
+            if !DiscardAction(*card).feeds_next_opponent(state) {
+                return (
+                    DiscardAction(*card),
+                    "the first card the next opponent cannot use",
+                );
+            }
+        }
+
+        // discard first hand by default
+        (
+            DiscardAction(hand[0]),
+            "the next opponent can use every card, the first one",
+        )
+    }
+}
+
+impl BurracoAgent for SmartAgent {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+        SmartAgent::draw_with_reason(state).0
+    }
+
     fn select_play_action(
         &mut self,
         actions: Vec<(PlayAction, i32)>,
         _state: &BurracoState,
     ) -> PlayAction {
-        let mut actions = actions;
-        actions.sort_by_key(|(a, d_score)| (SmartAgent::play_action_preference(a), *d_score));
-        actions.into_iter().next().unwrap().0
+        SmartAgent::ranked_plays(&actions).swap_remove(0).0
     }
 
     fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction {
-        let (team, _team_player) = state.curr_team_player();
-        let other_team_runs = &state.teams[(team + 1) % state.num_teams].played_runs;
+        SmartAgent::discard_with_reason(hand, state).0
+    }
 
-        let other_team_actions_now = PlayAction::enumerate(other_team_runs, &Cards(vec![]), 0);
+    fn explain_draw(&mut self, state: &BurracoState) -> Option<Explanation> {
+        let (action, reason) = SmartAgent::draw_with_reason(state);
+        Some(Explanation {
+            reason: reason.into(),
+            ranked: vec![Alternative::new(Action::Draw(action), 0.0, "")],
+        })
+    }
 
-        // discard first card that does not immediately give oppenent a benefit
-        for card in hand.iter() {
-            let other_team_actions_with_card =
-                PlayAction::enumerate(other_team_runs, &Cards(vec![*card]), 0);
-            if other_team_actions_with_card.len() == other_team_actions_now.len() {
-                return DiscardAction(*card);
-            }
-        }
+    fn explain_play(
+        &mut self,
+        actions: &[(PlayAction, i32)],
+        _state: &BurracoState,
+    ) -> Option<Explanation> {
+        let ranked = SmartAgent::ranked_plays(actions)
+            .into_iter()
+            .map(|(a, d_score)| {
+                let preference = SmartAgent::play_action_preference(&a);
+                let note = format!("preference {}", preference);
+                Alternative::new(Action::Play(a), d_score as f64, note)
+            })
+            .collect();
+        Some(Explanation {
+            reason: "plays wildcard replacements, new runs, appends and then ends the play, \
+                     the smallest score change first"
+                .into(),
+            ranked,
+        })
+    }
 
-        // discard first hand by default
-        DiscardAction(hand[0])
+    fn explain_discard(&mut self, hand: &Cards, state: &BurracoState) -> Option<Explanation> {
+        let (action, reason) = SmartAgent::discard_with_reason(hand, state);
+        Some(Explanation {
+            reason: reason.into(),
+            ranked: vec![Alternative::new(Action::Discard(action), 0.0, "")],
+        })
+    }
+
+    fn wants_to_ask_close(&mut self, view: &PlayerView) -> bool {
+        // few enough cards to go out this turn
+        view.hand.len() <= 3
     }
 
     fn display(&self) -> String {
@@ -156,14 +424,23 @@ impl BurracoAgent for SmartAgent {
 
 pub struct MaxAgent {}
 
+impl MaxAgent {
+    /// The largest score change first, the last of equal ones first
+    fn ranked_plays(actions: &[(PlayAction, i32)]) -> Vec<(PlayAction, i32)> {
+        let mut actions: Vec<_> = actions.iter().rev().cloned().collect();
+        actions.sort_by_key(|(_a, d_score)| Reverse(*d_score));
+        actions
+    }
+}
+
 impl BurracoAgent for MaxAgent {
     fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
         // TODO: some max calculation here of gain?
 
-        if state.round % 2 == 0 {
+        if state.round.is_multiple_of(2) {
             DrawAction::DrawPile
         } else {
-            DrawAction::DrawOpen
+            draw_open_if_allowed(state)
         }
     }
 
@@ -172,11 +449,38 @@ impl BurracoAgent for MaxAgent {
         actions: Vec<(PlayAction, i32)>,
         _state: &BurracoState,
     ) -> PlayAction {
-        let max_action = actions
-            .iter()
-            .max_by_key(|(_a, d_score)| d_score)
-            .expect("at least noop action");
-        max_action.clone().0
+        MaxAgent::ranked_plays(&actions).swap_remove(0).0
+    }
+
+    fn explain_play(
+        &mut self,
+        actions: &[(PlayAction, i32)],
+        _state: &BurracoState,
+    ) -> Option<Explanation> {
+        let ranked = MaxAgent::ranked_plays(actions)
+            .into_iter()
+            .map(|(a, d_score)| Alternative::new(Action::Play(a), d_score as f64, ""))
+            .collect();
+        Some(Explanation {
+            reason: "plays for the largest score change".into(),
+            ranked,
+        })
+    }
+
+    fn explain_draw(&mut self, state: &BurracoState) -> Option<Explanation> {
+        let action = self.select_draw_action(state);
+        Some(Explanation {
+            reason: "draws from the pile on even rounds, from the open pile on odd ones".into(),
+            ranked: vec![Alternative::new(Action::Draw(action), 0.0, "")],
+        })
+    }
+
+    fn explain_discard(&mut self, hand: &Cards, state: &BurracoState) -> Option<Explanation> {
+        let action = self.select_discard_action(hand, state);
+        Some(Explanation {
+            reason: "discards the first card".into(),
+            ranked: vec![Alternative::new(Action::Discard(action), 0.0, "")],
+        })
     }
 
     fn select_discard_action(&mut self, hand: &Cards, _state: &BurracoState) -> DiscardAction {
@@ -189,6 +493,721 @@ impl BurracoAgent for MaxAgent {
     }
 }
 
+pub struct LookaheadAgent {}
+
+impl LookaheadAgent {
+    /// The lowest cost first, a node per card, None once the budget is exhausted
+    fn ranked_discards_within(
+        hand: &Cards,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Vec<DiscardEstimate>> {
+        let mut estimates = Vec::with_capacity(hand.len());
+        for card in hand.iter() {
+            if !budget.spend(1) {
+                return None;
+            }
+            estimates.push(estimate_discard(state, *card));
+        }
+        estimates.sort_by(|a, b| a.cost().total_cmp(&b.cost()));
+        Some(estimates)
+    }
+
+    fn draw_explanation(estimate: &DrawEstimate) -> Explanation {
+        // the draw pile first, as it is drawn from when the values are equal
+        let mut ranked = vec![Alternative::new(
+            Action::Draw(DrawAction::DrawPile),
+            estimate.draw_pile,
+            "on average over the unseen cards",
+        )];
+        if let Some(open_pile) = estimate.open_pile {
+            let open = Alternative::new(Action::Draw(DrawAction::DrawOpen), open_pile, "");
+            ranked.push(open);
+        }
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Explanation {
+            reason: "draws for the best outlook of the hand, the highest value".into(),
+            ranked,
+        }
+    }
+
+    fn discard_explanation(ranked: Vec<DiscardEstimate>) -> Explanation {
+        let ranked = ranked
+            .into_iter()
+            .map(|e| {
+                let action = Action::Discard(DiscardAction(e.card));
+                Alternative::new(action, e.cost(), discard_estimate_note(&e))
+            })
+            .collect();
+        Explanation {
+            reason: "discards the card expected to help the others the least, the lowest cost"
+                .into(),
+            ranked,
+        }
+    }
+}
+
+/// The parts of the cost of a discard, see `DiscardEstimate`
+fn discard_estimate_note(estimate: &DiscardEstimate) -> String {
+    format!(
+        "opponent {:.1}, partner {:.1}, keep {:.1}",
+        estimate.opponent_gain, estimate.partner_gain, estimate.keep_value
+    )
+}
+
+impl BurracoAgent for LookaheadAgent {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+        estimate_draw(state).best()
+    }
+
+    fn select_draw_action_within(
+        &mut self,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Choice<DrawAction>> {
+        let estimate = estimate_draw_within(state, budget)?;
+        Some(Choice {
+            action: estimate.best(),
+            explanation: Some(LookaheadAgent::draw_explanation(&estimate)),
+        })
+    }
+
+    fn select_play_action(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+    ) -> PlayAction {
+        SmartAgent {}.select_play_action(actions, state)
+    }
+
+    fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction {
+        self.select_discard_action_within(hand, state, &mut Budget::unlimited())
+            .expect("unlimited budget")
+            .action
+    }
+
+    fn select_discard_action_within(
+        &mut self,
+        hand: &Cards,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Choice<DiscardAction>> {
+        // the card expected to help the others the least
+        let ranked = LookaheadAgent::ranked_discards_within(hand, state, budget)?;
+        let action = DiscardAction(ranked.first()?.card);
+        Some(Choice {
+            action,
+            explanation: Some(LookaheadAgent::discard_explanation(ranked)),
+        })
+    }
+
+    fn explain_draw(&mut self, state: &BurracoState) -> Option<Explanation> {
+        Some(LookaheadAgent::draw_explanation(&estimate_draw(state)))
+    }
+
+    fn explain_play(
+        &mut self,
+        actions: &[(PlayAction, i32)],
+        state: &BurracoState,
+    ) -> Option<Explanation> {
+        SmartAgent {}.explain_play(actions, state)
+    }
+
+    fn explain_discard(&mut self, hand: &Cards, state: &BurracoState) -> Option<Explanation> {
+        LookaheadAgent::ranked_discards_within(hand, state, &mut Budget::unlimited())
+            .map(LookaheadAgent::discard_explanation)
+    }
+
+    fn display(&self) -> String {
+        "Lookahead agent".into()
+    }
+}
+
+pub struct WeightedAgent {
+    pub weights: Weights,
+}
+
+impl WeightedAgent {
+    /// Weighted sum of the features of the hand and the team runs after the play, Noop
+    /// included, so a play is only made when it is worth more than keeping the cards
+    fn play_action_value(&self, game: &BurracoGame, action: &PlayAction, d_score: i32) -> f64 {
+        let w = &self.weights;
+        let outcome = match game.validate(action) {
+            Ok(outcome) => outcome,
+            Err(_) => return f64::NEG_INFINITY,
+        };
+        let run = match &outcome.run_change {
+            RunChange::None => None,
+            RunChange::New(run) | RunChange::Replace(_, run) => Some(run),
+        };
+        let wildcards = match action {
+            PlayAction::ReplaceWildcard(_, _, _) => -1,
+            _ => outcome
+                .cards_played
+                .iter()
+                .filter(|c| c.is_wildcard())
+                .count() as i32,
+        };
+        let (run_length, clean_length) = match run {
+            Some(run) if run.is_clean() => (run.cards().len(), run.cards().len().min(7)),
+            Some(run) => (run.cards().len(), 0),
+            None => (0, 0),
+        };
+        let cards_left = game.current_player().hand.len() - outcome.cards_played.len();
+
+        w.score * d_score as f64
+            + w.wildcard_use * wildcards as f64
+            + w.run_length * run_length as f64
+            + w.clean_burraco * clean_length as f64
+            + w.cards_left * cards_left as f64
+            + w.pot_proximity / (1 + cards_left) as f64
+    }
+
+    /// The highest value first, the last of equal ones first
+    fn ranked_plays(
+        &self,
+        actions: &[(PlayAction, i32)],
+        state: &BurracoState,
+    ) -> Vec<(PlayAction, f64)> {
+        let game = BurracoGame::at_phase(state.clone(), GamePhase::Play);
+        let mut ranked: Vec<_> = actions
+            .iter()
+            .rev()
+            // moves only shuffle the runs, as for the SmartAgent
+            .filter(|(a, _d_score)| !matches!(a, PlayAction::MoveCard(_, _, _)))
+            .map(|(a, d_score)| (a.clone(), self.play_action_value(&game, a, *d_score)))
+            .collect();
+        ranked.sort_by(|(_a, x), (_b, y)| y.total_cmp(x));
+        ranked
+    }
+
+    /// The lowest cost first, a node per card as for the `LookaheadAgent`, None once the
+    /// budget is exhausted
+    fn ranked_discards_within(
+        &self,
+        hand: &Cards,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Vec<(DiscardEstimate, f64)>> {
+        let w = &self.weights;
+        let mut ranked = Vec::with_capacity(hand.len());
+        for card in hand.iter() {
+            if !budget.spend(1) {
+                return None;
+            }
+            let e = estimate_discard(state, *card);
+            let cost = w.discard_danger * e.opponent_gain - e.partner_gain + e.keep_value;
+            ranked.push((e, cost));
+        }
+        ranked.sort_by(|(_a, x), (_b, y)| x.total_cmp(y));
+        Some(ranked)
+    }
+
+    fn discard_explanation(ranked: Vec<(DiscardEstimate, f64)>) -> Explanation {
+        let ranked = ranked
+            .into_iter()
+            .map(|(e, cost)| {
+                let action = Action::Discard(DiscardAction(e.card));
+                Alternative::new(action, cost, discard_estimate_note(&e))
+            })
+            .collect();
+        Explanation {
+            reason: "discards for the lowest weighted cost".into(),
+            ranked,
+        }
+    }
+}
+
+impl BurracoAgent for WeightedAgent {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+        SmartAgent {}.select_draw_action(state)
+    }
+
+    fn select_play_action(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+    ) -> PlayAction {
+        self.ranked_plays(&actions, state)
+            .into_iter()
+            .next()
+            .map(|(a, _value)| a)
+            .unwrap_or(PlayAction::Noop)
+    }
+
+    fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction {
+        self.select_discard_action_within(hand, state, &mut Budget::unlimited())
+            .expect("unlimited budget")
+            .action
+    }
+
+    fn select_discard_action_within(
+        &mut self,
+        hand: &Cards,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Choice<DiscardAction>> {
+        let ranked = self.ranked_discards_within(hand, state, budget)?;
+        let (best, _cost) = ranked.first()?;
+        Some(Choice {
+            action: DiscardAction(best.card),
+            explanation: Some(WeightedAgent::discard_explanation(ranked)),
+        })
+    }
+
+    fn explain_draw(&mut self, state: &BurracoState) -> Option<Explanation> {
+        SmartAgent {}.explain_draw(state)
+    }
+
+    fn explain_play(
+        &mut self,
+        actions: &[(PlayAction, i32)],
+        state: &BurracoState,
+    ) -> Option<Explanation> {
+        let ranked = self
+            .ranked_plays(actions, state)
+            .into_iter()
+            .map(|(a, value)| Alternative::new(Action::Play(a), value, ""))
+            .collect();
+        Some(Explanation {
+            reason: "plays for the highest weighted value of the hand and runs after the play"
+                .into(),
+            ranked,
+        })
+    }
+
+    fn explain_discard(&mut self, hand: &Cards, state: &BurracoState) -> Option<Explanation> {
+        self.ranked_discards_within(hand, state, &mut Budget::unlimited())
+            .map(WeightedAgent::discard_explanation)
+    }
+
+    fn display(&self) -> String {
+        "Weighted agent".into()
+    }
+}
+
+/// Runs this long are one or two cards from a burraco
+const NEAR_BURRACO: usize = 5;
+/// From this many cards left in hand the wildcards are no longer held back
+const FEW_CARDS_LEFT: usize = 6;
+/// Closing risk, see `closing_risk`, from which a big open pile is left alone
+const LATE_GAME_RISK: f64 = 0.25;
+/// Discard priority of a card that costs nothing to discard, see `discard_priority`
+const NO_DISCARD_COST: usize = 10;
+
+/// Plays by documented human strategy: holds the wildcards for burracos, builds clean runs
+/// towards seven cards, goes for the pot early and does not discard what the opponents need
+/// for their burracos. Keeps the reason for every choice in `log`.
+#[derive(Default)]
+pub struct ExpertAgent {
+    /// one line per draw, play and discard
+    pub log: Vec<String>,
+}
+
+impl ExpertAgent {
+    fn draw_with_reason(state: &BurracoState) -> (DrawAction, String) {
+        if !DrawAction::allowed(state).contains(&DrawAction::DrawOpen) {
+            return (
+                DrawAction::DrawPile,
+                "the open pile may not be taken".into(),
+            );
+        }
+        let (team, team_player) = state.curr_team_player();
+        let runs = &state.teams[team].played_runs;
+        let open = state.open_pile_draw();
+
+        let near_burraco = open.iter().find_map(|card| {
+            runs.iter()
+                .filter(|r| r.cards().len() >= NEAR_BURRACO && !r.is_burraco())
+                .find(|r| {
+                    r.append(&Cards(vec![*card]), Append::Top).is_ok()
+                        || r.append(&Cards(vec![*card]), Append::Bottom).is_ok()
+                })
+                .map(|r| (card, r))
+        });
+        if let Some((card, run)) = near_burraco {
+            let reason = format!("{} takes the run {} towards a burraco", card, run.cards());
+            return (DrawAction::DrawOpen, reason);
+        }
+
+        let mut hand = state.teams[team].players[team_player].hand.clone();
+        hand.extend(open.iter().cloned());
+        let actions = PlayAction::enumerate(&state.rules.run, runs, &hand, 0);
+        let usable = open
+            .iter()
+            .filter(|c| actions.iter().any(|(a, _)| a.cards_played().contains(c)))
+            .count();
+        if open.len() > FEW_CARDS_LEFT && closing_risk(state) >= LATE_GAME_RISK {
+            let reason = format!(
+                "{} open cards are too many to get rid of before the game closes",
+                open.len()
+            );
+            (DrawAction::DrawPile, reason)
+        } else if usable > 0 && 2 * usable >= open.len() {
+            let reason = format!("{} of the {} open cards are usable", usable, open.len());
+            (DrawAction::DrawOpen, reason)
+        } else {
+            let reason = format!(
+                "only {} of the {} open cards are usable",
+                usable,
+                open.len()
+            );
+            (DrawAction::DrawPile, reason)
+        }
+    }
+
+    /// Lower is better, None for the actions never made
+    fn play_priority(game: &BurracoGame, action: &PlayAction) -> Option<(usize, String)> {
+        let outcome = game.validate(action).ok()?;
+        let team = game.current_team();
+        let cards_left = game.current_player().hand.len() - outcome.cards_played.len();
+        let wildcards = outcome
+            .cards_played
+            .iter()
+            .filter(|c| c.is_wildcard())
+            .count();
+        let (run, was_burraco) = match &outcome.run_change {
+            RunChange::None => (None, false),
+            RunChange::New(run) => (Some(run), false),
+            RunChange::Replace(i, run) => (Some(run), team.played_runs[*i].is_burraco()),
+        };
+        let has_burraco =
+            team.played_runs.iter().any(|r| r.is_burraco()) || run.is_some_and(|r| r.is_burraco());
+
+        let priority = match (action, run) {
+            (PlayAction::MoveCard(_, _, _), _) => return None,
+            (PlayAction::Noop, _) => (90, "nothing worth playing, keeps the cards".into()),
+            _ if outcome.empties_hand && !team.has_reached_pot => {
+                (0, "empties the hand to take the pot".into())
+            }
+            _ if outcome.empties_hand && has_burraco => (0, "closes the game".into()),
+            _ if outcome.empties_hand => (99, "may not close without a burraco".into()),
+            (_, Some(run)) if run.is_burraco() && !was_burraco => {
+                let kind = if run.is_clean() { "clean" } else { "dirty" };
+                (10, format!("makes the {} burraco {}", kind, run.cards()))
+            }
+            (PlayAction::ReplaceWildcard(_, _, _), _) => {
+                (20, "takes the wildcard back to the hand".into())
+            }
+            _ if wildcards > 0 && cards_left > FEW_CARDS_LEFT => {
+                (95, "holds the wildcard for a burraco".into())
+            }
+            _ if wildcards > 0 => (60, "few cards left, uses the wildcard".into()),
+            (_, Some(run)) if run.is_clean() && run.cards().len() >= NEAR_BURRACO => (
+                30,
+                format!("takes the clean run {} towards a burraco", run.cards()),
+            ),
+            (PlayAction::StartRun(run), _) if run.run_type() == RunType::Sequence => {
+                (40, "starts a clean sequence".into())
+            }
+            (_, Some(run)) if run.is_clean() => {
+                (45, format!("extends the clean run {}", run.cards()))
+            }
+            _ => (50, "plays the natural cards".into()),
+        };
+        Some(priority)
+    }
+
+    /// Lower is better, with what discarding the card costs the hand or gives the opponents
+    fn discard_priority(state: &BurracoState, hand: &Cards, card: Card) -> (usize, String) {
+        let rules = &state.rules.run;
+        let curr_team = state.curr_team();
+        let feeds_near_burraco = state
+            .teams
+            .iter()
+            .enumerate()
+            .filter(|(t, _team)| *t != curr_team)
+            .flat_map(|(_t, team)| team.played_runs.iter())
+            .filter(|r| r.cards().len() >= NEAR_BURRACO && !r.is_burraco())
+            .find(|r| {
+                r.append(&Cards(vec![card]), Append::Top).is_ok()
+                    || r.append(&Cards(vec![card]), Append::Bottom).is_ok()
+            });
+        let keeps_with = hand
+            .iter()
+            .filter(|other| !other.is_wildcard() && **other != card)
+            .find(|other| could_run_with(&card, other, rules));
+
+        if let Some(run) = feeds_near_burraco {
+            (90, format!("feeds the opponents' run {}", run.cards()))
+        } else if DiscardAction(card).feeds_next_opponent(state) {
+            (80, "the next opponent could use it".into())
+        } else if card.is_wildcard() {
+            (70, "gives away a wildcard".into())
+        } else if let Some(other) = keeps_with {
+            (
+                40,
+                format!("parts it from {}, which it could run with", other),
+            )
+        } else {
+            (NO_DISCARD_COST, "fits nothing in the hand".into())
+        }
+    }
+
+    /// By priority, then the largest score change first
+    fn ranked_plays(
+        state: &BurracoState,
+        actions: &[(PlayAction, i32)],
+    ) -> Vec<(PlayAction, i32, (usize, String))> {
+        let game = BurracoGame::at_phase(state.clone(), GamePhase::Play);
+        let mut ranked: Vec<_> = actions
+            .iter()
+            .filter_map(|(a, d_score)| {
+                let priority = ExpertAgent::play_priority(&game, a)?;
+                Some((a.clone(), *d_score, priority))
+            })
+            .collect();
+        ranked.sort_by_key(|(_a, d_score, (priority, _reason))| (*priority, -d_score));
+        ranked
+    }
+
+    /// By priority, then the card worth the most left in hand when the game closes first
+    fn ranked_discards(state: &BurracoState, hand: &Cards) -> Vec<(Card, (usize, String))> {
+        let rules = &state.rules.run;
+        let mut ranked: Vec<_> = hand
+            .iter()
+            .map(|c| (*c, ExpertAgent::discard_priority(state, hand, *c)))
+            .collect();
+        ranked.sort_by_key(|(c, (priority, _reason))| (*priority, -rules.scoring.card_value(c.1)));
+        ranked
+    }
+}
+
+impl BurracoAgent for ExpertAgent {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+        self.select_draw_action_within(state, &mut Budget::unlimited())
+            .expect("always chooses")
+            .action
+    }
+
+    /// Logs the reason, as do the plays and discards, also when asked within a budget
+    fn select_draw_action_within(
+        &mut self,
+        state: &BurracoState,
+        _budget: &mut Budget,
+    ) -> Option<Choice<DrawAction>> {
+        let explanation = self.explain_draw(state)?;
+        let action = match explanation.best() {
+            Some(Action::Draw(action)) => *action,
+            _ => DrawAction::DrawPile,
+        };
+        self.log.push(format!("{}: {}", action, explanation.reason));
+        Some(Choice {
+            action,
+            explanation: Some(explanation),
+        })
+    }
+
+    fn select_play_action(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+    ) -> PlayAction {
+        self.select_play_action_within(actions, state, &mut Budget::unlimited())
+            .expect("always chooses")
+            .action
+    }
+
+    fn select_play_action_within(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+        _budget: &mut Budget,
+    ) -> Option<Choice<PlayAction>> {
+        let explanation = self.explain_play(&actions, state)?;
+        let action = match explanation.best() {
+            Some(Action::Play(action)) => action.clone(),
+            _ => PlayAction::Noop,
+        };
+        self.log.push(format!("{}: {}", action, explanation.reason));
+        Some(Choice {
+            action,
+            explanation: Some(explanation),
+        })
+    }
+
+    fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction {
+        self.select_discard_action_within(hand, state, &mut Budget::unlimited())
+            .expect("always chooses")
+            .action
+    }
+
+    fn select_discard_action_within(
+        &mut self,
+        hand: &Cards,
+        state: &BurracoState,
+        _budget: &mut Budget,
+    ) -> Option<Choice<DiscardAction>> {
+        let explanation = self.explain_discard(hand, state)?;
+        let action = match explanation.best() {
+            Some(Action::Discard(action)) => *action,
+            _ => panic!("game would have ended if empty hand"),
+        };
+        self.log.push(format!("{}: {}", action, explanation.reason));
+        Some(Choice {
+            action,
+            explanation: Some(explanation),
+        })
+    }
+
+    fn explain_draw(&mut self, state: &BurracoState) -> Option<Explanation> {
+        let (action, reason) = ExpertAgent::draw_with_reason(state);
+        Some(Explanation {
+            reason,
+            ranked: vec![Alternative::new(Action::Draw(action), 0.0, "")],
+        })
+    }
+
+    fn explain_play(
+        &mut self,
+        actions: &[(PlayAction, i32)],
+        state: &BurracoState,
+    ) -> Option<Explanation> {
+        let ranked: Vec<_> = ExpertAgent::ranked_plays(state, actions)
+            .into_iter()
+            .map(|(a, _d_score, (priority, reason))| {
+                Alternative::new(Action::Play(a), priority as f64, reason)
+            })
+            .collect();
+        let reason = match ranked.first() {
+            Some(best) => best.note.clone(),
+            None => "nothing to play".into(),
+        };
+        Some(Explanation { reason, ranked })
+    }
+
+    fn explain_discard(&mut self, hand: &Cards, state: &BurracoState) -> Option<Explanation> {
+        let ranked: Vec<_> = ExpertAgent::ranked_discards(state, hand)
+            .into_iter()
+            .map(|(c, (priority, reason))| {
+                Alternative::new(Action::Discard(DiscardAction(c)), priority as f64, reason)
+            })
+            .collect();
+        let reason = match ranked.first() {
+            Some(best) if best.score as usize == NO_DISCARD_COST => best.note.clone(),
+            Some(best) => format!(
+                "every card costs something, this one the least: {}",
+                best.note
+            ),
+            None => String::new(),
+        };
+        Some(Explanation { reason, ranked })
+    }
+
+    fn display(&self) -> String {
+        "Expert agent".into()
+    }
+}
+
+pub struct LinearPolicyAgent {
+    /// shared by the agents created from the same `AgentType`
+    pub policy: Arc<LinearPolicy>,
+}
+
+impl LinearPolicyAgent {
+    /// The highest policy score first, the first of equal ones first
+    fn ranked<T>(
+        &self,
+        state: &BurracoState,
+        candidates: Vec<(T, Action)>,
+    ) -> Vec<(T, Action, f64)> {
+        let view = state.player_view(state.player_turn);
+        let extractor = FeatureExtractor::new(&view);
+        let mut ranked: Vec<_> = candidates
+            .into_iter()
+            .map(|(candidate, action)| {
+                let score = self.policy.score(&extractor.extract(&action));
+                (candidate, action, score)
+            })
+            .collect();
+        ranked.sort_by(|(_a, _, x), (_b, _, y)| y.total_cmp(x));
+        ranked
+    }
+
+    fn best<T>(&self, state: &BurracoState, candidates: Vec<(T, Action)>) -> T {
+        self.ranked(state, candidates)
+            .into_iter()
+            .next()
+            .expect("at least one candidate")
+            .0
+    }
+
+    fn explain(&self, state: &BurracoState, actions: Vec<Action>) -> Explanation {
+        let candidates = actions.into_iter().map(|a| ((), a)).collect();
+        let ranked = self
+            .ranked(state, candidates)
+            .into_iter()
+            .map(|((), action, score)| Alternative::new(action, score, ""))
+            .collect();
+        Explanation {
+            reason: "the highest score of the linear policy".into(),
+            ranked,
+        }
+    }
+}
+
+impl BurracoAgent for LinearPolicyAgent {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+        let candidates = DrawAction::allowed(state)
+            .into_iter()
+            .map(|a| (a, Action::Draw(a)))
+            .collect();
+        self.best(state, candidates)
+    }
+
+    fn select_play_action(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+    ) -> PlayAction {
+        let candidates = actions
+            .into_iter()
+            .map(|(a, _d_score)| (a.clone(), Action::Play(a)))
+            .collect();
+        self.best(state, candidates)
+    }
+
+    fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction {
+        let candidates = hand
+            .iter()
+            .map(|c| (DiscardAction(*c), Action::Discard(DiscardAction(*c))))
+            .collect();
+        self.best(state, candidates)
+    }
+
+    fn explain_draw(&mut self, state: &BurracoState) -> Option<Explanation> {
+        let actions = DrawAction::allowed(state)
+            .into_iter()
+            .map(Action::Draw)
+            .collect();
+        Some(self.explain(state, actions))
+    }
+
+    fn explain_play(
+        &mut self,
+        actions: &[(PlayAction, i32)],
+        state: &BurracoState,
+    ) -> Option<Explanation> {
+        let actions = actions
+            .iter()
+            .map(|(a, _d_score)| Action::Play(a.clone()))
+            .collect();
+        Some(self.explain(state, actions))
+    }
+
+    fn explain_discard(&mut self, hand: &Cards, state: &BurracoState) -> Option<Explanation> {
+        let actions = hand
+            .iter()
+            .map(|c| Action::Discard(DiscardAction(*c)))
+            .collect();
+        Some(self.explain(state, actions))
+    }
+
+    fn display(&self) -> String {
+        "Linear policy agent".into()
+    }
+}
+
 use rand::prelude::SliceRandom;
 use rand::prelude::StdRng;
 use rand::prelude::ThreadRng;
@@ -205,11 +1224,11 @@ pub fn random_agent_thread_rng() -> RandomAgent<ThreadRng> {
 }
 
 impl<R: Rng + ?Sized> BurracoAgent for RandomAgent<R> {
-    fn select_draw_action(&mut self, _state: &BurracoState) -> DrawAction {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
         if self.rng.gen::<bool>() {
             DrawAction::DrawPile
         } else {
-            DrawAction::DrawOpen
+            draw_open_if_allowed(state)
         }
     }
 
@@ -218,15 +1237,15 @@ impl<R: Rng + ?Sized> BurracoAgent for RandomAgent<R> {
         actions: Vec<(PlayAction, i32)>,
         _state: &BurracoState,
     ) -> PlayAction {
-        if actions.len() == 1 {
-            actions[0].clone().0
-        } else {
-            (&actions[1..])
-                .choose(&mut self.rng)
-                .expect("we know at least noop exists")
-                .clone()
-                .0
-        }
+        // Noop only when there is nothing else, it is left out when a card has to be used
+        let plays: Vec<_> = actions
+            .into_iter()
+            .filter(|(a, _d_score)| *a != PlayAction::Noop)
+            .collect();
+        plays
+            .choose(&mut self.rng)
+            .map(|(a, _d_score)| a.clone())
+            .unwrap_or(PlayAction::Noop)
     }
 
     fn select_discard_action(&mut self, hand: &Cards, _state: &BurracoState) -> DiscardAction {
@@ -242,6 +1261,146 @@ impl<R: Rng + ?Sized> BurracoAgent for RandomAgent<R> {
     }
 }
 
+/// Plays as the agent it wraps, but makes a random move instead at the mistake rate, a
+/// deliberate mistake, see `Difficulty`
+pub struct MistakenAgent<R: Rng> {
+    pub agent: Box<dyn BurracoAgent>,
+    /// of the draws, plays and discards
+    pub mistake_rate: f64,
+    pub rng: R,
+}
+
+impl<R: Rng> MistakenAgent<R> {
+    fn draw_mistake(&mut self, state: &BurracoState) -> Option<DrawAction> {
+        if !self.rng.gen_bool(self.mistake_rate) {
+            return None;
+        }
+        DrawAction::allowed(state).choose(&mut self.rng).copied()
+    }
+
+    fn play_mistake(&mut self, actions: &[(PlayAction, i32)]) -> Option<PlayAction> {
+        if !self.rng.gen_bool(self.mistake_rate) {
+            return None;
+        }
+        // moves only shuffle the runs, no mistake there
+        let plays: Vec<_> = actions
+            .iter()
+            .filter(|(a, _d_score)| !matches!(a, PlayAction::MoveCard(_, _, _)))
+            .collect();
+        plays.choose(&mut self.rng).map(|(a, _d_score)| a.clone())
+    }
+
+    fn discard_mistake(&mut self, hand: &Cards) -> Option<DiscardAction> {
+        if !self.rng.gen_bool(self.mistake_rate) {
+            return None;
+        }
+        hand.choose(&mut self.rng).map(|c| DiscardAction(*c))
+    }
+}
+
+/// A random move of a `MistakenAgent`, explained as the mistake it is
+fn mistake<T: Clone>(action: T, as_action: impl FnOnce(T) -> Action) -> Choice<T> {
+    let explanation = Explanation {
+        reason: "a deliberate mistake, a random move".into(),
+        ranked: vec![Alternative::new(as_action(action.clone()), 0.0, "")],
+    };
+    Choice {
+        action,
+        explanation: Some(explanation),
+    }
+}
+
+impl<R: Rng> BurracoAgent for MistakenAgent<R> {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+        self.draw_mistake(state)
+            .unwrap_or_else(|| self.agent.select_draw_action(state))
+    }
+
+    fn select_play_action(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+    ) -> PlayAction {
+        match self.play_mistake(&actions) {
+            Some(action) => action,
+            None => self.agent.select_play_action(actions, state),
+        }
+    }
+
+    fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction {
+        self.discard_mistake(hand)
+            .unwrap_or_else(|| self.agent.select_discard_action(hand, state))
+    }
+
+    fn select_draw_action_within(
+        &mut self,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Choice<DrawAction>> {
+        match self.draw_mistake(state) {
+            Some(action) => Some(mistake(action, Action::Draw)),
+            None => self.agent.select_draw_action_within(state, budget),
+        }
+    }
+
+    fn select_play_action_within(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Choice<PlayAction>> {
+        match self.play_mistake(&actions) {
+            Some(action) => Some(mistake(action, Action::Play)),
+            None => self.agent.select_play_action_within(actions, state, budget),
+        }
+    }
+
+    fn select_discard_action_within(
+        &mut self,
+        hand: &Cards,
+        state: &BurracoState,
+        budget: &mut Budget,
+    ) -> Option<Choice<DiscardAction>> {
+        match self.discard_mistake(hand) {
+            Some(action) => Some(mistake(action, Action::Discard)),
+            None => self.agent.select_discard_action_within(hand, state, budget),
+        }
+    }
+
+    /// What the agent would do without its mistakes
+    fn explain_draw(&mut self, state: &BurracoState) -> Option<Explanation> {
+        self.agent.explain_draw(state)
+    }
+
+    fn explain_play(
+        &mut self,
+        actions: &[(PlayAction, i32)],
+        state: &BurracoState,
+    ) -> Option<Explanation> {
+        self.agent.explain_play(actions, state)
+    }
+
+    fn explain_discard(&mut self, hand: &Cards, state: &BurracoState) -> Option<Explanation> {
+        self.agent.explain_discard(hand, state)
+    }
+
+    fn wants_to_ask_close(&mut self, view: &PlayerView) -> bool {
+        self.agent.wants_to_ask_close(view)
+    }
+
+    fn answer_close_query(&mut self, view: &PlayerView) -> bool {
+        self.agent.answer_close_query(view)
+    }
+
+    fn display(&self) -> String {
+        format!(
+            "{} making {:.0}% mistakes",
+            self.agent.display(),
+            self.mistake_rate * 100.0
+        )
+    }
+}
+
 pub struct ManualCliAgent {}
 use std::io;
 use std::io::Write;
@@ -284,7 +1443,7 @@ impl fmt::Display for ConciseStateView {
             writeln!(w, "Current team:")?;
             write!(w, " h: ")?;
             for team_player in &curr_team.players {
-                print!("[{}] ", team_player.hand.len());
+                write!(w, "[{}] ", team_player.hand.len())?;
             }
             writeln!(w)?;
             for (i, run) in curr_team.played_runs.iter().enumerate() {
@@ -292,14 +1451,16 @@ impl fmt::Display for ConciseStateView {
             }
         }
 
-        writeln!(
+        write!(
             w,
-            "Piles: <0>{} <1>[{}] (pots: [{}] [{}])",
+            "Piles: <0>{} <1>[{}] (pots:",
             self.state.open_pile,
             self.state.draw_pile.len(),
-            self.state.pot1.len(),
-            self.state.pot1.len()
         )?;
+        for pot in self.state.pots.iter() {
+            write!(w, " [{}]", pot.len())?;
+        }
+        writeln!(w, ")")?;
         let hand = &self.state.teams[team].players[player].hand;
         writeln!(w, "Hand: {}", hand)?;
         writeln!(
@@ -323,8 +1484,56 @@ impl ManualCliAgent {
             state: state.clone(),
         }
     }
+
+    /// For the `hint [AGENT]` command, the hints of the expert unless another agent is named,
+    /// each with its index among the choices
+    fn print_hints(state: &BurracoState, phase: GamePhase, choices: &[Action], agent: &str) {
+        let config = match agent {
+            "" => Ok(HintConfig::default()),
+            name => name.parse().map(|agent| HintConfig {
+                agent,
+                ..HintConfig::default()
+            }),
+        };
+        let view = state.player_view(state.player_turn);
+        match config.and_then(|config| hints(&view, phase, &config)) {
+            Ok(hints) => {
+                for hint in hints {
+                    let index = choices.iter().position(|c| *c == hint.action);
+                    match index {
+                        Some(index) => println!(" {} - {}: {}", index, hint.action, hint.reason),
+                        None => println!(" {}: {}", hint.action, hint.reason),
+                    }
+                }
+            }
+            Err(e) => println!("No hints: {}", e),
+        }
+    }
+
+    fn read_yes_no() -> bool {
+        let mut choice = String::new();
+        loop {
+            print!("> ");
+            io::stdout().flush().expect("to flush ok");
+            io::stdin()
+                .read_line(&mut choice)
+                .expect("Failed to read line");
+
+            match choice.trim() {
+                "y" => return true,
+                "n" => return false,
+                _ => {
+                    println!("Please answer y or n (got: '{}')", choice.trim());
+                    choice.clear();
+                }
+            }
+        }
+    }
 }
 
+/// The command asking for hints, with the agent to ask after it if any
+const HINT_COMMAND: &str = "hint";
+
 impl BurracoAgent for ManualCliAgent {
     fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
         println!("[{}]", self.display());
@@ -332,7 +1541,10 @@ impl BurracoAgent for ManualCliAgent {
         println!("Select a draw action:");
         println!(" <0> - Draw from open pile");
         println!(" <1> - Draw from hidden pile");
-        println!("then press ENTER");
+        println!(
+            "then press ENTER, or enter {} [AGENT] for hints",
+            HINT_COMMAND
+        );
 
         let mut choice = String::new();
 
@@ -344,6 +1556,12 @@ impl BurracoAgent for ManualCliAgent {
                 .expect("Failed to read line");
             println!();
 
+            if let Some(agent) = choice.trim().strip_prefix(HINT_COMMAND) {
+                let choices = [DrawAction::DrawOpen, DrawAction::DrawPile].map(Action::Draw);
+                ManualCliAgent::print_hints(state, GamePhase::Draw, &choices, agent.trim());
+                choice.clear();
+                continue;
+            }
             let action = match choice.trim() {
                 "0" => DrawAction::DrawOpen,
                 "1" => DrawAction::DrawPile,
@@ -353,6 +1571,11 @@ impl BurracoAgent for ManualCliAgent {
                     continue;
                 }
             };
+            if !DrawAction::allowed(state).contains(&action) {
+                println!("Draw action not allowed by the rules: {}", action);
+                choice.clear();
+                continue;
+            }
             return action;
         }
     }
@@ -366,7 +1589,10 @@ impl BurracoAgent for ManualCliAgent {
         ManualCliAgent::display_concise_state(state);
         println!("Select a play action:");
         print_play_actions(&actions, &state.teams[state.curr_team()].played_runs);
-        println!("then press ENTER");
+        println!(
+            "then press ENTER, or enter {} [AGENT] for hints",
+            HINT_COMMAND
+        );
 
         let mut choice = String::new();
 
@@ -379,6 +1605,15 @@ impl BurracoAgent for ManualCliAgent {
                 .expect("Failed to read line");
             println!();
 
+            if let Some(agent) = choice.trim().strip_prefix(HINT_COMMAND) {
+                let choices: Vec<_> = actions
+                    .iter()
+                    .map(|(a, _)| Action::Play(a.clone()))
+                    .collect();
+                ManualCliAgent::print_hints(state, GamePhase::Play, &choices, agent.trim());
+                choice.clear();
+                continue;
+            }
             let choice_idx: usize = if let Ok(idx) = choice.trim().parse() {
                 idx
             } else {
@@ -403,7 +1638,10 @@ impl BurracoAgent for ManualCliAgent {
         for (idx, card) in hand.iter().enumerate() {
             println!(" {} - {}", idx, card);
         }
-        println!("then press ENTER");
+        println!(
+            "then press ENTER, or enter {} [AGENT] for hints",
+            HINT_COMMAND
+        );
 
         let mut choice = String::new();
 
@@ -415,6 +1653,15 @@ impl BurracoAgent for ManualCliAgent {
                 .expect("Failed to read line");
             println!();
 
+            if let Some(agent) = choice.trim().strip_prefix(HINT_COMMAND) {
+                let choices: Vec<_> = hand
+                    .iter()
+                    .map(|c| Action::Discard(DiscardAction(*c)))
+                    .collect();
+                ManualCliAgent::print_hints(state, GamePhase::Discard, &choices, agent.trim());
+                choice.clear();
+                continue;
+            }
             let choice_idx: usize = if let Ok(idx) = choice.trim().parse() {
                 idx
             } else {
@@ -438,7 +1685,271 @@ impl BurracoAgent for ManualCliAgent {
         }
     }
 
+    fn wants_to_ask_close(&mut self, view: &PlayerView) -> bool {
+        println!("[{}]", self.display());
+        println!("Hand: {}", view.hand);
+        println!("Ask your partner: may I close? (y/n)");
+        ManualCliAgent::read_yes_no()
+    }
+
+    fn answer_close_query(&mut self, view: &PlayerView) -> bool {
+        println!("[{}]", self.display());
+        println!("Hand: {}", view.hand);
+        println!("Partner P{} asks: may I close? (y/n)", view.player_turn);
+        ManualCliAgent::read_yes_no()
+    }
+
     fn display(&self) -> String {
         "Manual commandline agent".into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Rank::*;
+    use crate::model::Suit::*;
+    use crate::rules::Rules;
+    use crate::scenario::ScenarioBuilder;
+
+    #[test]
+    fn test_random_without_noop() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4, ♠5, ♠6, ♠7")
+            .phase(GamePhase::Play)
+            .build()?;
+        // as when a card from the open pile has to be used
+        let actions: Vec<_> = game
+            .play_actions(0)
+            .into_iter()
+            .filter(|(a, _d_score)| *a != PlayAction::Noop)
+            .take(2)
+            .collect();
+        assert_eq!(2, actions.len());
+        let mut agent = RandomAgent {
+            rng: StdRng::seed_from_u64(0),
+        };
+        let chosen: Vec<_> = (0..20)
+            .map(|_| agent.select_play_action(actions.clone(), game.state()))
+            .collect();
+        assert!(actions.iter().all(|(a, _d_score)| chosen.contains(a)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expert_draw() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4, ♥9, ♣K")
+            .run(0, "♥3, ♥4, ♥5, ♥6, ♥7")
+            .open_pile("♦K, ♥8")
+            .build()?;
+        let mut agent = ExpertAgent::default();
+        assert_eq!(DrawAction::DrawOpen, agent.select_draw_action(game.state()));
+        assert_eq!(
+            Some(
+                &"Collect open pile: ♥8 takes the run [♥3, ♥4, ♥5, ♥6, ♥7, ] towards a burraco"
+                    .into()
+            ),
+            agent.log.last()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_expert_holds_wildcard() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4, ♠5, JK, ♥9, ♣K, ♦3, ♥Q, ♣7, ♦J, ♠10, ♥5")
+            .phase(GamePhase::Play)
+            .build()?;
+        let mut agent = ExpertAgent::default();
+        let actions = game.play_actions(0);
+        assert!(actions.len() > 1);
+        assert_eq!(
+            PlayAction::Noop,
+            agent.select_play_action(actions, game.state())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_expert_logs_budgeted_decisions() -> Result<(), String> {
+        use crate::budget::decide_discard;
+        use crate::budget::decide_draw;
+        use crate::budget::decide_play;
+
+        let mut game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4, ♠5, ♠6, ♥9, ♣K")
+            .build()?;
+        let mut agent = ExpertAgent::default();
+        let mut budget = Budget::unlimited();
+        let draw = decide_draw(&mut agent, game.state(), &mut budget).action;
+        game.draw(draw)?;
+        let actions = game.play_actions(0);
+        let play = decide_play(&mut agent, actions, game.state(), &mut budget).action;
+        game.play(play.clone())?;
+        if game.phase() == GamePhase::Play {
+            game.play(PlayAction::Noop)?;
+        }
+        let hand = game.current_player().hand.clone();
+        let discard = decide_discard(&mut agent, &hand, game.state(), &mut budget).action;
+
+        assert_eq!(3, agent.log.len());
+        assert!(agent.log[0].starts_with(&format!("{}: ", draw)));
+        assert!(agent.log[1].starts_with(&format!("{}: ", play)));
+        assert!(agent.log[2].starts_with(&format!("{}: ", discard)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_expert_discard() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♣10, ♥K")
+            .run(1, "♣5, ♣6, ♣7, ♣8, ♣9")
+            .phase(GamePhase::Discard)
+            .build()?;
+        let mut agent = ExpertAgent::default();
+        let hand = game.current_player().hand.clone();
+        assert_eq!(
+            DiscardAction(Card(Hearts, King)),
+            agent.select_discard_action(&hand, game.state())
+        );
+        assert_eq!(
+            (90, "feeds the opponents' run [♣5, ♣6, ♣7, ♣8, ♣9, ]".into()),
+            ExpertAgent::discard_priority(game.state(), &hand, Card(Clubs, Numerical(10)))
+        );
+        assert_eq!(
+            Some(&"Discard ♥K: fits nothing in the hand".into()),
+            agent.log.last()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_explanations_rank_the_choice_first() -> Result<(), String> {
+        let agent_types = [
+            AgentType::Smart,
+            AgentType::Max,
+            AgentType::Lookahead,
+            AgentType::Weighted(Weights::default()),
+            AgentType::Expert,
+            AgentType::Linear(Arc::new(LinearPolicy::default())),
+        ];
+        for seed in 0..3 {
+            for agent_type in agent_types.iter() {
+                let mut agent = create_agent(agent_type.clone());
+                let mut game = BurracoGame::from(BurracoState::init_seeded(2, 2, Some(seed))?);
+                let first = |explanation: Option<Explanation>| {
+                    explanation.and_then(|e| e.ranked.into_iter().next().map(|a| a.action))
+                };
+
+                let draw = first(agent.explain_draw(game.state()));
+                assert_eq!(
+                    Some(Action::Draw(agent.select_draw_action(game.state()))),
+                    draw
+                );
+                game.draw(DrawAction::DrawPile)?;
+
+                let actions = game.play_actions(0);
+                let play = first(agent.explain_play(&actions, game.state()));
+                let action = agent.select_play_action(actions, game.state());
+                assert_eq!(Some(Action::Play(action)), play, "{}", agent.display());
+
+                let mut game = BurracoGame::at_phase(game.state().clone(), GamePhase::Discard);
+                let hand = game.discard_choices();
+                let discard = first(agent.explain_discard(&hand, game.state()));
+                let action = agent.select_discard_action(&hand, game.state());
+                assert_eq!(Some(Action::Discard(action)), discard);
+                game.discard(action)?;
+            }
+        }
+        assert_eq!(
+            None,
+            DumbAgent {}.explain_draw(&BurracoState::init_with(2, 2)?)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_agent_type() {
+        assert!(matches!("smart".parse(), Ok(AgentType::Smart)));
+        assert!(matches!(
+            "level-easy".parse(),
+            Ok(AgentType::Level(Difficulty::Easy))
+        ));
+        assert!("level-hardest".parse::<AgentType>().is_err());
+        assert!("no-such-file.policy".parse::<AgentType>().is_err());
+        assert!("no-such-file.weights".parse::<AgentType>().is_err());
+        assert_eq!(
+            Some("Unknown agent smrt".into()),
+            "smrt".parse::<AgentType>().err()
+        );
+    }
+
+    #[test]
+    fn test_mistaken_agent() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♣10, ♥K, ♥Q, ♥J")
+            .phase(GamePhase::Discard)
+            .build()?;
+        let hand = game.discard_choices();
+        let mut always = MistakenAgent {
+            agent: Box::new(DumbAgent {}),
+            mistake_rate: 1.0,
+            rng: StdRng::seed_from_u64(0),
+        };
+        let discards: Vec<_> = (0..20)
+            .map(|_| always.select_discard_action(&hand, game.state()))
+            .collect();
+        assert!(discards.iter().all(|d| hand.contains(&d.0)));
+        assert!(discards.iter().any(|d| *d != DiscardAction(hand[0])));
+
+        // told as the mistake it is, not as what the agent would have done
+        let choice = always
+            .select_discard_action_within(&hand, game.state(), &mut Budget::unlimited())
+            .expect("an unlimited budget");
+        let explanation = choice.explanation.expect("explained");
+        assert_eq!("a deliberate mistake, a random move", explanation.reason);
+        assert_eq!(Some(&Action::Discard(choice.action)), explanation.best());
+
+        let mut never = MistakenAgent {
+            mistake_rate: 0.0,
+            ..always
+        };
+        for _ in 0..20 {
+            let discard = never.select_discard_action(&hand, game.state());
+            assert_eq!(DiscardAction(hand[0]), discard);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_explain_discard() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♣10, ♥K")
+            .run(1, "♣5, ♣6, ♣7, ♣8, ♣9")
+            .phase(GamePhase::Discard)
+            .build()?;
+        let hand = game.current_player().hand.clone();
+        let explanation = ExpertAgent::default()
+            .explain_discard(&hand, game.state())
+            .expect("explained");
+        assert_eq!(
+            "Why: fits nothing in the hand\n  \
+             1. Discard ♥K: 10.00 (fits nothing in the hand)\n  \
+             2. Discard ♣10: 90.00 (feeds the opponents' run [♣5, ♣6, ♣7, ♣8, ♣9, ])\n",
+            explanation.to_string()
+        );
+
+        // when every card feeds the opponents, the least harmful one goes
+        let hand = Cards::of("♣10, ♣4")?;
+        let explanation = ExpertAgent::default()
+            .explain_discard(&hand, game.state())
+            .expect("explained");
+        assert_eq!(
+            "every card costs something, this one the least: \
+             feeds the opponents' run [♣5, ♣6, ♣7, ♣8, ♣9, ]",
+            explanation.reason
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::actions::DiscardAction;
+use crate::actions::DrawAction;
+use crate::actions::PlayAction;
+use crate::agent::BurracoAgent;
+use crate::agent::Choice;
+use crate::agent::Explanation;
+use crate::model::BurracoState;
+use crate::model::Cards;
+
+/// Milliseconds from some fixed point in time, e.g. `Date.now()` in a browser
+pub type Clock = fn() -> f64;
+
+/// Milliseconds since first called, from `std::time::Instant`, which browsers do not have
+pub fn system_clock() -> f64 {
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_secs_f64() * 1000.0
+}
+
+/// Shared by the harness and an agent, so the harness can stop a decision from elsewhere
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// What an agent may spend on a decision: wall time, searched nodes or both, until cancelled.
+///
+/// An agent spends the budget as it searches and gives up once it is exhausted, after which
+/// the harness makes a default action, see `decide_draw`. The harness also makes the default
+/// action when the budget ran out while the agent chose, whether it spent it or not.
+#[derive(Debug, Clone)]
+pub struct Budget {
+    clock: Clock,
+    /// in milliseconds of the clock
+    deadline: Option<f64>,
+    max_nodes: Option<usize>,
+    nodes: usize,
+    cancel: CancelToken,
+}
+
+impl Budget {
+    pub fn unlimited() -> Budget {
+        Budget {
+            clock: system_clock,
+            deadline: None,
+            max_nodes: None,
+            nodes: 0,
+            cancel: CancelToken::default(),
+        }
+    }
+
+    /// Milliseconds from now by the system clock
+    pub fn time(millis: f64) -> Budget {
+        Budget::time_with(millis, system_clock)
+    }
+
+    /// Milliseconds from now by the clock
+    pub fn time_with(millis: f64, clock: Clock) -> Budget {
+        Budget {
+            clock,
+            deadline: Some(clock() + millis),
+            ..Budget::unlimited()
+        }
+    }
+
+    pub fn nodes(max_nodes: usize) -> Budget {
+        Budget::unlimited().with_max_nodes(max_nodes)
+    }
+
+    pub fn with_max_nodes(self, max_nodes: usize) -> Budget {
+        Budget {
+            max_nodes: Some(max_nodes),
+            ..self
+        }
+    }
+
+    pub fn with_cancel(self, cancel: CancelToken) -> Budget {
+        Budget { cancel, ..self }
+    }
+
+    /// Counts the nodes about to be searched, false when that exhausts the budget
+    pub fn spend(&mut self, nodes: usize) -> bool {
+        self.nodes += nodes;
+        !self.is_exhausted()
+    }
+
+    pub fn nodes_spent(&self) -> usize {
+        self.nodes
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.cancel.is_cancelled()
+            || self.max_nodes.is_some_and(|max| self.nodes > max)
+            || self
+                .deadline
+                .is_some_and(|deadline| (self.clock)() > deadline)
+    }
+}
+
+/// The action of a decision within a budget
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decision<T> {
+    pub action: T,
+    /// the agent ran out of budget and the default action was made instead
+    pub fell_back: bool,
+    /// why the agent chose the action, None when it cannot tell or fell back
+    pub explanation: Option<Explanation>,
+}
+
+impl<T> Decision<T> {
+    /// The default action when the agent gave up, or overran the budget while choosing
+    fn of(choice: Option<Choice<T>>, budget: &Budget, default: impl FnOnce() -> T) -> Decision<T> {
+        match choice {
+            Some(choice) if !budget.is_exhausted() => Decision {
+                action: choice.action,
+                fell_back: false,
+                explanation: choice.explanation,
+            },
+            _ => Decision {
+                action: default(),
+                fell_back: true,
+                explanation: None,
+            },
+        }
+    }
+}
+
+/// Asks the agent for a draw within the budget, or else draws from the draw pile
+pub fn decide_draw(
+    agent: &mut dyn BurracoAgent,
+    state: &BurracoState,
+    budget: &mut Budget,
+) -> Decision<DrawAction> {
+    let choice = agent.select_draw_action_within(state, budget);
+    Decision::of(choice, budget, || DrawAction::DrawPile)
+}
+
+/// Asks the agent for a play within the budget, or else ends the play with Noop when allowed
+/// and makes the first action otherwise
+pub fn decide_play(
+    agent: &mut dyn BurracoAgent,
+    actions: Vec<(PlayAction, i32)>,
+    state: &BurracoState,
+    budget: &mut Budget,
+) -> Decision<PlayAction> {
+    let default = if actions.iter().any(|(a, _)| *a == PlayAction::Noop) {
+        PlayAction::Noop
+    } else {
+        actions[0].0.clone()
+    };
+    let choice = agent.select_play_action_within(actions, state, budget);
+    Decision::of(choice, budget, || default)
+}
+
+/// Asks the agent for a discard within the budget, or else discards the first of the cards
+pub fn decide_discard(
+    agent: &mut dyn BurracoAgent,
+    hand: &Cards,
+    state: &BurracoState,
+    budget: &mut Budget,
+) -> Decision<DiscardAction> {
+    let choice = agent.select_discard_action_within(hand, state, budget);
+    Decision::of(choice, budget, || DiscardAction(hand[0]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::Action;
+    use crate::actions::BurracoGame;
+    use crate::agent::LookaheadAgent;
+    use crate::agent::SmartAgent;
+    use crate::agent::WeightedAgent;
+    use crate::weights::Weights;
+    use std::sync::atomic::AtomicU64;
+
+    /// Ten milliseconds later every time it is read
+    fn ticking_clock() -> f64 {
+        static NOW: AtomicU64 = AtomicU64::new(0);
+        NOW.fetch_add(10, Ordering::Relaxed) as f64
+    }
+
+    #[test]
+    fn test_budget() {
+        let mut nodes = Budget::nodes(2);
+        assert!(nodes.spend(1));
+        assert!(nodes.spend(1));
+        assert!(!nodes.spend(1));
+        assert_eq!(3, nodes.nodes_spent());
+
+        let mut time = Budget::time_with(25.0, ticking_clock);
+        assert!(!time.is_exhausted());
+        assert!(time.spend(1));
+        assert!(time.is_exhausted());
+
+        let cancel = CancelToken::default();
+        let unlimited = Budget::unlimited().with_cancel(cancel.clone());
+        assert!(!unlimited.is_exhausted());
+        cancel.cancel();
+        assert!(unlimited.is_exhausted());
+    }
+
+    #[test]
+    fn test_decide() -> Result<(), String> {
+        let state = BurracoState::init_seeded(2, 2, Some(0))?;
+        let mut agent = LookaheadAgent {};
+
+        let mut unlimited = Budget::unlimited();
+        let decision = decide_draw(&mut agent, &state, &mut unlimited);
+        assert!(!decision.fell_back);
+        assert_eq!(agent.select_draw_action(&state), decision.action);
+        assert!(unlimited.nodes_spent() > 0);
+        // explained by the same search that decided
+        let explanation = decision.explanation.expect("explained");
+        assert_eq!(Some(&Action::Draw(decision.action)), explanation.best());
+
+        let mut none = Budget::nodes(0);
+        assert_eq!(
+            Decision {
+                action: DrawAction::DrawPile,
+                fell_back: true,
+                explanation: None,
+            },
+            decide_draw(&mut agent, &state, &mut none)
+        );
+        let hand = state.teams[0].players[0].hand.clone();
+        let decision = decide_discard(&mut agent, &hand, &state, &mut Budget::nodes(3));
+        assert_eq!(DiscardAction(hand[0]), decision.action);
+        assert!(decision.fell_back);
+        let mut weighted = WeightedAgent {
+            weights: Weights::default(),
+        };
+        let decision = decide_discard(&mut weighted, &hand, &state, &mut Budget::nodes(3));
+        assert!(decision.fell_back);
+        let mut unlimited = Budget::unlimited();
+        let decision = decide_discard(&mut weighted, &hand, &state, &mut unlimited);
+        assert!(!decision.fell_back);
+        assert_eq!(hand.len(), unlimited.nodes_spent());
+
+        // agents that do not search ignore the budget
+        let mut game = BurracoGame::from(state);
+        game.draw(DrawAction::DrawPile).expect("draw");
+        let actions = game.play_actions(0);
+        let mut budget = Budget::nodes(0);
+        let decision = decide_play(
+            &mut SmartAgent {},
+            actions.clone(),
+            game.state(),
+            &mut budget,
+        );
+        assert!(!decision.fell_back);
+        let explanation = decision.explanation.expect("explained");
+        assert_eq!(Some(&Action::Play(decision.action)), explanation.best());
+
+        // but not once they overrun it
+        let mut overrun = Budget::time_with(5.0, ticking_clock);
+        let decision = decide_play(&mut SmartAgent {}, actions, game.state(), &mut overrun);
+        assert!(decision.fell_back);
+        assert_eq!(PlayAction::Noop, decision.action);
+        Ok(())
+    }
+}
@@ -1,8 +1,12 @@
+use crate::actions::Action;
 use crate::actions::BurracoGame;
 use crate::actions::DiscardAction;
 use crate::actions::DrawAction;
 use crate::actions::PlayAction;
 use crate::actions::PlayAction::*;
+use crate::agent::Alternative;
+use crate::agent::Explanation;
+use crate::error::BurracoError;
 use crate::model::BurracoState;
 use crate::model::Card;
 use crate::model::Cards;
@@ -70,9 +74,9 @@ impl fmt::Display for Run {
 impl fmt::Display for BurracoState {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         writeln!(f, "Burraco table:")?;
-        for t in 0..self.num_teams {
+        for t in 0..self.rules.table.num_teams() {
             writeln!(f, "  Team {}", t)?;
-            for p in 0..self.num_team_players {
+            for p in 0..self.rules.table.num_team_players() {
                 writeln!(
                     f,
                     "    Player {}-{}: {} cards",
@@ -88,11 +92,16 @@ impl fmt::Display for BurracoState {
         }
         writeln!(f, "  Draw pile: {} cards", self.draw_pile.len())?;
         writeln!(f, "  Open pile: {}", self.open_pile)?;
+        if let Some(card) = self.must_use {
+            writeln!(f, "  Must use: {}", card)?;
+        }
         writeln!(f)?;
-        writeln!(f, "  Pot 1: {} cards", self.pot1.len())?;
-        writeln!(f, "  Pot 2: {} cards", self.pot2.len())?;
+        for (i, pot) in self.pots.iter().enumerate() {
+            writeln!(f, "  Pot {}: {} cards", i + 1, pot.len())?;
+        }
         writeln!(f, "Cards tot: {}", self.cards_total())?;
         let (team, player) = self.player_team_idxs[self.player_turn];
+        writeln!(f, "Dealer: P{}", self.dealer)?;
         writeln!(f, "Current round: {}", self.round)?;
         writeln!(f, "Current turn: team {} player {}", team, player)
     }
@@ -157,8 +166,87 @@ pub fn print_play_actions(actions: &[(PlayAction, i32)], runs: &[Run]) {
     }
 }
 
+/// The reason and the best of the ranked alternatives
+pub fn print_explanation(explanation: &Explanation, max_alternatives: usize) {
+    println!("Why: {}", explanation.reason);
+    for (i, alternative) in explanation.ranked.iter().take(max_alternatives).enumerate() {
+        println!("  {}. {}", i + 1, alternative);
+    }
+    if explanation.ranked.len() > max_alternatives {
+        println!("  ({} more)", explanation.ranked.len() - max_alternatives);
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Why: {}", self.reason)?;
+        for (i, alternative) in self.ranked.iter().enumerate() {
+            writeln!(f, "  {}. {}", i + 1, alternative)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Alternative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {:.2}", self.action, self.score)?;
+        if !self.note.is_empty() {
+            write!(f, " ({})", self.note)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Action {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Action::Draw(draw) => write!(f, "{}", draw),
+            Action::Play(play) => write!(f, "{}", play),
+            Action::Discard(discard) => write!(f, "{}", discard),
+        }
+    }
+}
+
 impl fmt::Display for DiscardAction {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Discard {}", self.0)
     }
 }
+
+impl fmt::Display for BurracoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BurracoError::WrongPhase(phase) => {
+                write!(f, "Action invalid when phase is: {:?}", phase)
+            }
+            BurracoError::CardsNotInHand(cards) => write!(f, "Cards not in hand: {}", cards),
+            BurracoError::InvalidRunIndex(run_idx) => {
+                write!(f, "Non-existing run index: {}", run_idx)
+            }
+            BurracoError::InvalidRun(reason) => write!(f, "Invalid run: {}", reason),
+            BurracoError::InvalidMove(reason) => write!(f, "Invalid move: {}", reason),
+            BurracoError::MustUseCard(card) => {
+                write!(f, "Must first play the card from the open pile: {}", card)
+            }
+            BurracoError::WildcardClose(card) => {
+                write!(f, "Cannot close by discarding a wildcard: {}", card)
+            }
+            BurracoError::DiscardPicked(card) => {
+                write!(
+                    f,
+                    "Cannot discard the card just picked from the open pile: {}",
+                    card
+                )
+            }
+            BurracoError::FeedsOpponent(card) => {
+                write!(
+                    f,
+                    "Cannot discard last a card the opponent can use: {}",
+                    card
+                )
+            }
+            BurracoError::CloseRefused => write!(f, "Partner answered no to closing"),
+            BurracoError::NoBurracoToClose => write!(f, "Cannot close without a burraco"),
+        }
+    }
+}
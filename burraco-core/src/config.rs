@@ -0,0 +1,82 @@
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Reads the `name = value` lines of a config, as the `Weights` and the `LinearPolicy` are
+/// kept in, where `#` starts a comment. `set` takes each name and value and tells whether the
+/// name is known, `what` is what the names are called in the errors.
+pub fn parse_values<F>(config: &str, what: &str, mut set: F) -> Result<(), String>
+where
+    F: FnMut(&str, f64) -> bool,
+{
+    for (i, line) in config.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| format!("line {}: expected `name = value`", i + 1))?;
+        let name = name.trim();
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("line {}: invalid value for {}", i + 1, name))?;
+        if !set(name, value) {
+            return Err(format!("line {}: unknown {} {}", i + 1, what, name));
+        }
+    }
+    Ok(())
+}
+
+/// Parses the config file at `path`, the errors naming the file
+pub fn load<T: FromStr<Err = String>>(path: impl AsRef<Path>) -> Result<T, String> {
+    let path = path.as_ref();
+    let config = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    config
+        .parse()
+        .map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+/// Writes the config to the file at `path`, as it displays
+pub fn save(config: &impl Display, path: impl AsRef<Path>) -> Result<(), String> {
+    let path = path.as_ref();
+    fs::write(path, config.to_string()).map_err(|e| format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_values() -> Result<(), String> {
+        let mut values = Vec::new();
+        parse_values(
+            "# comment\n\n a = -12 \nb=0.5 # note\n",
+            "value",
+            |name, value| {
+                values.push((name.to_string(), value));
+                true
+            },
+        )?;
+        assert_eq!(
+            vec![("a".to_string(), -12.0), ("b".to_string(), 0.5)],
+            values
+        );
+
+        assert_eq!(
+            Err("line 2: unknown weight b".into()),
+            parse_values("a = 1\nb = 2", "weight", |name, _| name == "a")
+        );
+        assert_eq!(
+            Err("line 1: invalid value for a".into()),
+            parse_values("a = high", "weight", |_, _| true)
+        );
+        assert_eq!(
+            Err("line 1: expected `name = value`".into()),
+            parse_values("a", "weight", |_, _| true)
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
+use crate::agent::AgentType;
+use crate::tournament::win_counts;
+
+/// How far a measured win rate may drift from the calibrated one, see `Difficulty::check_win_rate`
+pub const CALIBRATION_STANDARD_ERRORS: f64 = 3.0;
+
+/// Levels of play for casual players, each an agent configuration.
+///
+/// The harder levels plan more and keep better track of the cards, the easier ones make
+/// deliberate mistakes at a set rate, see `MistakenAgent`. Each level has a win rate against
+/// the `SmartAgent` measured in tournaments, see `calibrated_win_rate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    /// The agent the level plays by, apart from its mistakes
+    pub fn agent_type(self) -> AgentType {
+        match self {
+            Difficulty::Easy | Difficulty::Medium => AgentType::Smart,
+            // human strategy
+            Difficulty::Hard => AgentType::Expert,
+            // looks ahead over the cards not seen yet
+            Difficulty::Expert => AgentType::Lookahead,
+        }
+    }
+
+    /// Share of the draws, plays and discards made at random instead
+    pub fn mistake_rate(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.6,
+            Difficulty::Medium => 0.05,
+            Difficulty::Hard | Difficulty::Expert => 0.0,
+        }
+    }
+
+    /// Share of the games won against the `SmartAgent`, 1 vs 1 over the seeds 0..1000, as
+    /// measured by `measure_win_rate` in the calibrate example
+    pub fn calibrated_win_rate(self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.277,
+            Difficulty::Medium => 0.468,
+            Difficulty::Hard => 0.744,
+            Difficulty::Expert => 0.847,
+        }
+    }
+
+    /// Check a win rate measured over `num_games` by `measure_win_rate`, an error when it is
+    /// further from `calibrated_win_rate` than `CALIBRATION_STANDARD_ERRORS` standard errors
+    pub fn check_win_rate(self, win_rate: f64, num_games: u64) -> Result<(), String> {
+        let calibrated = self.calibrated_win_rate();
+        let standard_error = (calibrated * (1.0 - calibrated) / num_games as f64).sqrt();
+        if (win_rate - calibrated).abs() > CALIBRATION_STANDARD_ERRORS * standard_error {
+            return Err(format!(
+                "{} won {:.1}% of the games, calibrated at {:.1}%",
+                self,
+                win_rate * 100.0,
+                calibrated * 100.0
+            ));
+        }
+        Ok(())
+    }
+
+    /// Share of the 1 vs 1 games the level wins against the `SmartAgent`, playing first in
+    /// the games of half the seeds and second in the others
+    pub fn measure_win_rate(self, seeds: Range<u64>) -> Result<f64, String> {
+        let smart = [AgentType::Smart];
+        let mid = seeds.start + (seeds.end - seeds.start) / 2;
+        let mut wins = 0;
+        for seed in seeds.clone() {
+            // the mistakes seeded by the game too, or every game would repeat them
+            let level = [AgentType::SeededLevel(self, seed)];
+            wins += if seed < mid {
+                win_counts(&[&level, &smart], seed..seed + 1)?[0]
+            } else {
+                win_counts(&[&smart, &level], seed..seed + 1)?[1]
+            };
+        }
+        let num_games = seeds.end - seeds.start;
+        Ok(wins as f64 / num_games as f64)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Difficulty::ALL
+            .iter()
+            .find(|level| level.to_string().eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| format!("No difficulty {}", name))
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        for level in Difficulty::ALL.iter() {
+            assert_eq!(Ok(*level), level.to_string().parse());
+        }
+        assert_eq!(Ok(Difficulty::Hard), "hard".parse());
+        assert_eq!(
+            Err("No difficulty impossible".into()),
+            "impossible".parse::<Difficulty>()
+        );
+    }
+
+    #[test]
+    fn test_calibrated_win_rates_increase() {
+        let rates: Vec<_> = Difficulty::ALL
+            .iter()
+            .map(|level| level.calibrated_win_rate())
+            .collect();
+        assert!(rates.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    #[ignore = "plays 4000 games, run with --release -- --ignored"]
+    fn test_calibrated_win_rates_hold() -> Result<(), String> {
+        for level in Difficulty::ALL.iter() {
+            level.check_win_rate(level.measure_win_rate(0..1000)?, 1000)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::actions::GamePhase;
+use crate::model::Card;
+use crate::model::Cards;
+
+/// Reasons for the game to reject an action
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BurracoError {
+    /// the action does not belong to the current phase
+    WrongPhase(GamePhase),
+    /// some of the cards are not in the current player's hand
+    CardsNotInHand(Cards),
+    /// the current team has no run at this index
+    InvalidRunIndex(usize),
+    /// the cards do not form a valid run
+    InvalidRun(String),
+    /// the move itself makes no sense, e.g. moving a card onto itself
+    InvalidMove(String),
+    /// the card taken from the open pile has to be played first
+    MustUseCard(Card),
+    /// the game may not be closed by discarding a wildcard
+    WildcardClose(Card),
+    /// the single card taken from the open pile may not be discarded in the same turn
+    DiscardPicked(Card),
+    /// the next opponent could attach the last card of the hand to their runs
+    FeedsOpponent(Card),
+    /// the partner answered no to "may I close?"
+    CloseRefused,
+    /// the team has no burraco yet, which the rules require to close
+    NoBurracoToClose,
+}
+
+impl std::error::Error for BurracoError {}
+
+impl From<String> for BurracoError {
+    fn from(reason: String) -> Self {
+        BurracoError::InvalidRun(reason)
+    }
+}
+
+impl From<BurracoError> for String {
+    fn from(error: BurracoError) -> Self {
+        error.to_string()
+    }
+}
@@ -0,0 +1,426 @@
+use crate::actions::BurracoGame;
+use crate::actions::DrawAction;
+use crate::actions::GamePhase;
+use crate::actions::PlayAction;
+use crate::budget::Budget;
+use crate::model::BurracoState;
+use crate::model::Card;
+use crate::model::CardCounts;
+use crate::model::Cards;
+use crate::model::Rank;
+use crate::model::Run;
+use crate::rules::AcePolicy;
+use crate::rules::OpenPileDraw;
+use crate::rules::RunRules;
+
+/// The cards the player cannot see: the other hands, the pots and the draw pile
+pub fn unseen_cards(state: &BurracoState, player: usize) -> CardCounts {
+    let mut counts = Cards::build_decks(&state.rules.deck).deck_counts();
+    let mut seen = |cards: &Cards| {
+        for card in cards.iter() {
+            let count = &mut counts[card.deck_index()];
+            *count = count.saturating_sub(1);
+        }
+    };
+    let (team, team_player) = state.player_team_idxs[player];
+    seen(&state.teams[team].players[team_player].hand);
+    for team in state.teams.iter() {
+        for run in team.played_runs.iter() {
+            seen(run.cards());
+        }
+    }
+    seen(&state.open_pile);
+    counts
+}
+
+/// Positions of the rank in a sequence, the Ace may be low or high depending on the rules
+fn sequence_positions(rank: Rank, ace_policy: AcePolicy) -> Vec<i16> {
+    match (rank, ace_policy) {
+        (Rank::Ace, AcePolicy::LowOnly) => vec![1],
+        (Rank::Ace, AcePolicy::HighOnly) => vec![14],
+        (Rank::Ace, _) => vec![1, 14],
+        _ => vec![rank.index()],
+    }
+}
+
+/// Whether the two cards could be part of the same run
+pub(crate) fn could_run_with(card: &Card, other: &Card, rules: &RunRules) -> bool {
+    if card.is_wildcard() || other.is_wildcard() {
+        return true;
+    }
+    if rules.allow_groups && card.1 == other.1 {
+        return true;
+    }
+    card.0 == other.0
+        && sequence_positions(card.1, rules.ace_policy)
+            .iter()
+            .any(|a| {
+                sequence_positions(other.1, rules.ace_policy)
+                    .iter()
+                    .any(|b| (1..=2).contains(&(a - b).abs()))
+            })
+}
+
+/// Chance that a hand of `hand_size` unseen cards holds two of the `related` ones, enough to
+/// make a run with a third card
+fn chance_of_run(related: usize, unseen: usize, hand_size: usize) -> f64 {
+    if unseen == 0 || hand_size < 2 {
+        return 0.0;
+    }
+    let p = (related as f64 / unseen as f64).min(1.0);
+    let h = hand_size as i32;
+    let none = (1.0 - p).powi(h);
+    let one = h as f64 * p * (1.0 - p).powi(h - 1);
+    (1.0 - none - one).max(0.0)
+}
+
+/// Best score gained by attaching the card to the runs
+fn attach_gain(rules: &RunRules, runs: &[Run], card: Card) -> f64 {
+    PlayAction::enumerate(rules, runs, &Cards(vec![card]), 0)
+        .iter()
+        .map(|(_action, d_score)| *d_score)
+        .max()
+        .unwrap_or(0)
+        .max(0) as f64
+}
+
+/// What discarding a card is expected to be worth to the others, in points
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiscardEstimate {
+    pub card: Card,
+    /// to the next opponent, who may pick it up first
+    pub opponent_gain: f64,
+    /// to the partner playing after the next opponent, if the opponent leaves it
+    pub partner_gain: f64,
+    /// lost by no longer having the card in hand
+    pub keep_value: f64,
+}
+
+impl DiscardEstimate {
+    /// Lower is a better discard
+    pub fn cost(&self) -> f64 {
+        self.opponent_gain - self.partner_gain + self.keep_value
+    }
+}
+
+/// Estimates discarding a card of the current player, from the runs on the table and the
+/// cards the player has not seen
+pub fn estimate_discard(state: &BurracoState, card: Card) -> DiscardEstimate {
+    let rules = &state.rules.run;
+    let player = state.player_turn;
+    let num_players = state.player_team_idxs.len();
+    let hand_size = |seat: usize| {
+        let (t, p) = state.player_team_idxs[seat];
+        state.teams[t].players[p].hand.len()
+    };
+
+    let unseen = unseen_cards(state, player);
+    let num_unseen: usize = unseen.iter().sum();
+    let deck = Cards::build_deck(1);
+    let related: usize = deck
+        .iter()
+        .zip(unseen.iter())
+        .filter(|(other, _count)| could_run_with(&card, other, rules))
+        .map(|(_other, count)| count)
+        .sum();
+    let run_value = 3.0 * rules.scoring.card_value(card.1) as f64;
+
+    let opponent = (player + 1) % num_players;
+    let opponent_team = state.player_team_idxs[opponent].0;
+    let opponent_attach = attach_gain(rules, &state.teams[opponent_team].played_runs, card);
+    let opponent_run = chance_of_run(related, num_unseen, hand_size(opponent));
+    let opponent_gain = opponent_attach.max(opponent_run * run_value);
+
+    let (team, team_player) = state.player_team_idxs[player];
+    let partner = (player + 2) % num_players;
+    let partner_gain = if partner != player && state.player_team_idxs[partner].0 == team {
+        let passed = if opponent_attach > 0.0 {
+            0.0
+        } else {
+            1.0 - opponent_run
+        };
+        let partner_attach = attach_gain(rules, &state.teams[team].played_runs, card);
+        let partner_run = chance_of_run(related, num_unseen, hand_size(partner));
+        passed * partner_attach.max(partner_run * run_value)
+    } else {
+        0.0
+    };
+
+    let hand = &state.teams[team].players[team_player].hand;
+    let keep_value = if card.is_wildcard() {
+        run_value
+    } else {
+        let in_hand = hand
+            .iter()
+            .filter(|other| *other != &card && could_run_with(&card, other, rules))
+            .count();
+        in_hand.min(2) as f64 / 2.0 * run_value
+    };
+
+    DiscardEstimate {
+        card,
+        opponent_gain,
+        partner_gain,
+        keep_value,
+    }
+}
+
+/// Plays looked ahead when valuing a hand after taking the open pile
+const MAX_PLAYS: usize = 4;
+/// Cost of each card left in hand while the team is still to reach a pot
+const POT_DELAY_PER_CARD: f64 = 3.0;
+/// What a run of 7 cards is worth when getting closer to it, on top of the card values
+const BURRACO_PROGRESS: f64 = 100.0;
+
+/// What a hand is expected to be worth after playing greedily from it
+#[derive(Debug, Clone, PartialEq)]
+struct Outlook {
+    played: i32,
+    runs: Vec<Run>,
+    left: Cards,
+}
+
+/// Plays the best scoring action up to `max_plays` times, from the hand of the current player
+fn outlook(state: &BurracoState, hand: Cards, must_use: Option<Card>, max_plays: usize) -> Outlook {
+    let (team, team_player) = state.curr_team_player();
+    let mut state = state.clone();
+    state.teams[team].players[team_player].hand = hand;
+    state.must_use = must_use;
+    let reached_pot = state.teams[team].has_reached_pot;
+    let mut game = BurracoGame::at_phase(state, GamePhase::Play);
+
+    let mut played = 0;
+    for _i in 0..max_plays {
+        let best = game
+            .play_actions(0)
+            .into_iter()
+            .filter(|(a, _d_score)| *a != PlayAction::Noop)
+            .max_by_key(|(_a, d_score)| *d_score);
+        match best.map(|(action, d_score)| (game.play(action), d_score)) {
+            Some((Ok(()), d_score)) => played += d_score,
+            _ => break,
+        }
+        // a pot changes the hand entirely
+        if game.phase() != GamePhase::Play || game.current_team().has_reached_pot != reached_pot {
+            break;
+        }
+    }
+    Outlook {
+        played,
+        runs: game.current_team().played_runs.clone(),
+        left: game.current_player().hand.clone(),
+    }
+}
+
+/// How close the runs are to being burracos, which only pays off at 7 cards
+fn burraco_progress(runs: &[Run]) -> f64 {
+    runs.iter()
+        .filter(|r| !r.is_burraco())
+        .map(|r| (r.cards().len().min(6) as f64 / 7.0).powi(2) * BURRACO_PROGRESS)
+        .sum()
+}
+
+/// Rough chance that the game closes before the current player gets to empty the hand
+pub fn closing_risk(state: &BurracoState) -> f64 {
+    let team = state.curr_team();
+    let num_players = state.player_team_idxs.len();
+    let pots_left = state.pots.iter().any(|p| !p.is_empty());
+    let closest_opponent = state
+        .teams
+        .iter()
+        .enumerate()
+        .filter(|(t, other)| *t != team && (other.has_reached_pot || !pots_left))
+        .flat_map(|(_t, other)| other.players.iter().map(|p| p.hand.len()))
+        .min();
+    let by_opponent = closest_opponent.map_or(0.0, |hand_size| 1.0 / (1.0 + hand_size as f64));
+    let by_draw_pile = 1.0 / (1.0 + (state.draw_pile.len() / num_players) as f64);
+    by_opponent.max(by_draw_pile)
+}
+
+/// Points of the outlook: scored, burraco progress, less what is left in hand
+fn outlook_value(state: &BurracoState, outlook: &Outlook, risk: f64) -> f64 {
+    let rules = &state.rules.run;
+    let pot_delay = if !state.teams[state.curr_team()].has_reached_pot
+        && state.pots.iter().any(|p| !p.is_empty())
+    {
+        POT_DELAY_PER_CARD * outlook.left.len() as f64
+    } else {
+        0.0
+    };
+    outlook.played as f64 + burraco_progress(&outlook.runs)
+        - risk * outlook.left.value_sum_with(rules.scoring) as f64
+        - pot_delay
+}
+
+/// What each draw is expected to be worth to the current player, in points gained over
+/// playing from the hand as it is
+#[derive(Debug, Clone, PartialEq)]
+pub struct DrawEstimate {
+    /// taking what the rules allow of the open pile, if they allow it
+    pub open_pile: Option<f64>,
+    /// on average over the cards the player has not seen
+    pub draw_pile: f64,
+}
+
+impl DrawEstimate {
+    pub fn best(&self) -> DrawAction {
+        match self.open_pile {
+            Some(open_pile) if open_pile > self.draw_pile => DrawAction::DrawOpen,
+            _ => DrawAction::DrawPile,
+        }
+    }
+}
+
+/// Estimates taking the open pile against a blind draw, from hand bloat, the penalty points
+/// that could be left in hand, progress towards burracos and how soon a pot is reached
+pub fn estimate_draw(state: &BurracoState) -> DrawEstimate {
+    estimate_draw_within(state, &mut Budget::unlimited()).expect("unlimited budget")
+}
+
+/// As `estimate_draw`, spending a node of the budget per outlook, None once it is exhausted
+pub fn estimate_draw_within(state: &BurracoState, budget: &mut Budget) -> Option<DrawEstimate> {
+    let (team, team_player) = state.curr_team_player();
+    let hand = &state.teams[team].players[team_player].hand;
+    let risk = closing_risk(state);
+    let mut value = |cards: &[Card], must_use: Option<Card>, max_plays: usize| {
+        if !budget.spend(1) {
+            return None;
+        }
+        let mut hand = hand.clone();
+        hand.extend(cards.iter().cloned());
+        Some(outlook_value(
+            state,
+            &outlook(state, hand, must_use, max_plays),
+            risk,
+        ))
+    };
+
+    let open_pile = if DrawAction::allowed(state).contains(&DrawAction::DrawOpen) {
+        let must_use = match state.rules.open_pile_draw {
+            OpenPileDraw::WholePileMustUseTop => state.open_pile.last().cloned(),
+            _ => None,
+        };
+        Some(value(state.open_pile_draw(), must_use, MAX_PLAYS)? - value(&[], None, MAX_PLAYS)?)
+    } else {
+        None
+    };
+
+    // a single card rarely gives more than one play
+    let unseen = unseen_cards(state, state.player_turn);
+    let num_unseen: usize = unseen.iter().sum();
+    let baseline = value(&[], None, 1)?;
+    let mut draw_pile = 0.0;
+    for (card, count) in Cards::build_deck(1).iter().zip(unseen.iter()) {
+        if *count > 0 {
+            draw_pile += *count as f64 * (value(&[*card], None, 1)? - baseline);
+        }
+    }
+
+    Some(DrawEstimate {
+        open_pile,
+        draw_pile: draw_pile / num_unseen.max(1) as f64,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::Rank::*;
+    use crate::model::Suit::*;
+    use crate::rules::Rules;
+    use crate::rules::TableVariant;
+    use crate::scenario::ScenarioBuilder;
+
+    #[test]
+    fn test_unseen_cards() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4, ♠4, ♥9")
+            .run(1, "♥8, ♥9, ♥10")
+            .open_pile("JK")
+            .build()?;
+        let unseen = unseen_cards(game.state(), 0);
+        assert_eq!(0, unseen[Card(Spades, Numerical(4)).deck_index()]);
+        assert_eq!(0, unseen[Card(Hearts, Numerical(9)).deck_index()]);
+        assert_eq!(1, unseen[Card(Hearts, Numerical(8)).deck_index()]);
+        assert_eq!(3, unseen[Card(Jokers, Joker).deck_index()]);
+        assert_eq!(108 - 3 - 3 - 1, unseen.iter().sum::<usize>());
+        Ok(())
+    }
+
+    #[test]
+    fn test_chance_of_run() {
+        assert_eq!(0.0, chance_of_run(0, 50, 11));
+        assert_eq!(0.0, chance_of_run(10, 50, 1));
+        assert!(chance_of_run(20, 50, 11) > chance_of_run(5, 50, 11));
+        assert!(chance_of_run(5, 50, 11) > chance_of_run(5, 50, 3));
+    }
+
+    #[test]
+    fn test_estimate_discard() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♥J, ♣K, ♦3, ♦4")
+            .run(0, "♣4, ♣5, ♣6")
+            .run(1, "♥8, ♥9, ♥10")
+            .build()?;
+        let state = game.state();
+        let feeds_opponent = estimate_discard(state, Card(Hearts, Jack));
+        assert!(feeds_opponent.opponent_gain >= 10.0);
+        assert_eq!(0.0, feeds_opponent.partner_gain);
+        let feeds_partner = estimate_discard(state, Card(Clubs, Numerical(7)));
+        assert!(feeds_partner.partner_gain > 0.0);
+        let kept = estimate_discard(state, Card(Diamonds, Numerical(3)));
+        assert!(kept.keep_value > 0.0);
+        assert!(feeds_opponent.cost() > estimate_discard(state, Card(Clubs, King)).cost());
+
+        let one_vs_one = Rules {
+            table: TableVariant::OneVsOne,
+            ..Rules::default()
+        };
+        let game = ScenarioBuilder::new(one_vs_one)
+            .hand(0, "♣7, ♥2")
+            .run(0, "♣4, ♣5, ♣6")
+            .build()?;
+        assert_eq!(
+            0.0,
+            estimate_discard(game.state(), Card(Clubs, Numerical(7))).partner_gain
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate_draw() -> Result<(), String> {
+        let useful = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4, ♠5, ♥K, ♣9")
+            .open_pile("♦Q, ♠6, ♠7")
+            .build()?;
+        let estimate = estimate_draw(useful.state());
+        assert!(estimate.open_pile > Some(estimate.draw_pile));
+        assert_eq!(DrawAction::DrawOpen, estimate.best());
+
+        let bloat = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4, ♠5, ♥K, ♣9")
+            .open_pile("♥3, ♦6, ♣J, ♠K, ♥8, ♦A, ♣3, ♠10, ♥Q, ♦9")
+            .build()?;
+        assert_eq!(DrawAction::DrawPile, estimate_draw(bloat.state()).best());
+
+        let top_card_only = Rules {
+            open_pile_draw: OpenPileDraw::TopCardOnly,
+            ..Rules::default()
+        };
+        let empty = ScenarioBuilder::new(top_card_only).open_pile("").build()?;
+        assert_eq!(None, estimate_draw(empty.state()).open_pile);
+        Ok(())
+    }
+
+    #[test]
+    fn test_closing_risk() -> Result<(), String> {
+        let early = ScenarioBuilder::new(Rules::default()).build()?;
+        let late = ScenarioBuilder::new(Rules::default())
+            .hand(1, "♠4, ♠5")
+            .reached_pot(1)
+            .build()?;
+        assert!(closing_risk(late.state()) > closing_risk(early.state()));
+        assert!(closing_risk(late.state()) <= 1.0);
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+use crate::actions::Action;
+use crate::actions::DrawAction;
+use crate::actions::PlayAction;
+use crate::model::Append;
+use crate::model::Card;
+use crate::model::Cards;
+use crate::model::PlayerView;
+use crate::model::Run;
+use crate::model::Suit;
+use crate::rules::OpenPileDraw;
+
+pub const NUM_FEATURES: usize = 36;
+
+pub type Features = [f64; NUM_FEATURES];
+
+/// What each feature is, in the order of `Features`
+pub static FEATURE_NAMES: [&str; NUM_FEATURES] = [
+    // what the player knows of the game
+    "hand_clubs",
+    "hand_diamonds",
+    "hand_hearts",
+    "hand_spades",
+    "hand_wildcards",
+    "hand_size",
+    "hand_value",
+    "team_runs",
+    "team_longest_run",
+    "team_burracos",
+    "team_clean_runs",
+    "opponent_longest_run",
+    "opponent_burracos",
+    "open_pile_size",
+    "draw_pile_size",
+    "pots_left",
+    "team_reached_pot",
+    "opponent_reached_pot",
+    "opponent_smallest_hand",
+    "score_difference",
+    // the candidate action
+    "draw_open",
+    "draw_pile",
+    "start_run",
+    "append",
+    "replace_wildcard",
+    "move_card",
+    "noop",
+    "discard",
+    "action_cards",
+    "action_wildcards",
+    "action_value",
+    "run_length_after",
+    "run_clean_after",
+    "run_burraco_after",
+    "discard_fits_opponent",
+    // always 1, for the intercept of a linear model
+    "bias",
+];
+
+const NUM_STATE_FEATURES: usize = 20;
+
+// indices of the features of the candidate action, as named in `FEATURE_NAMES`
+/// the first of the flags for the kind of action, from `draw_open` to `discard`
+const ACTION_KIND: usize = NUM_STATE_FEATURES;
+const ACTION_CARDS: usize = ACTION_KIND + 8;
+const ACTION_WILDCARDS: usize = ACTION_CARDS + 1;
+const ACTION_VALUE: usize = ACTION_CARDS + 2;
+const RUN_LENGTH_AFTER: usize = ACTION_CARDS + 3;
+const RUN_CLEAN_AFTER: usize = ACTION_CARDS + 4;
+const RUN_BURRACO_AFTER: usize = ACTION_CARDS + 5;
+const DISCARD_FITS_OPPONENT: usize = ACTION_CARDS + 6;
+const BIAS: usize = NUM_FEATURES - 1;
+
+/// Turns a player view plus candidate actions into fixed-size vectors, see `FEATURE_NAMES`.
+///
+/// Features are counts, points or 0 and 1 flags, unscaled. The features of the view are
+/// worked out once for all the candidates.
+pub struct FeatureExtractor<'a> {
+    view: &'a PlayerView<'a>,
+    state_features: [f64; NUM_STATE_FEATURES],
+}
+
+impl<'a> FeatureExtractor<'a> {
+    pub fn new(view: &'a PlayerView<'a>) -> FeatureExtractor<'a> {
+        let scoring = view.rules.run.scoring;
+        let hand = view.hand;
+        let suit_count = |suit: Suit| {
+            hand.iter()
+                .filter(|c| c.0 == suit && !c.is_wildcard())
+                .count()
+        };
+        let longest = |runs: &[Run]| runs.iter().map(|r| r.cards().len()).max().unwrap_or(0);
+        let burracos = |runs: &[Run]| runs.iter().filter(|r| r.is_burraco()).count();
+        let table_score = |runs: &[Run]| runs.iter().map(|r| r.score()).sum::<i32>();
+        let opponent_runs = || view.opponents.iter().map(|o| o.played_runs);
+
+        let team_runs = view.team.played_runs;
+        let best_opponent_score = opponent_runs().map(table_score).max().unwrap_or(0);
+        let state_features = [
+            suit_count(Suit::Clubs) as f64,
+            suit_count(Suit::Diamonds) as f64,
+            suit_count(Suit::Hearts) as f64,
+            suit_count(Suit::Spades) as f64,
+            hand.iter().filter(|c| c.is_wildcard()).count() as f64,
+            hand.len() as f64,
+            hand.value_sum_with(scoring) as f64,
+            team_runs.len() as f64,
+            longest(team_runs) as f64,
+            burracos(team_runs) as f64,
+            team_runs.iter().filter(|r| r.is_clean()).count() as f64,
+            opponent_runs().map(longest).max().unwrap_or(0) as f64,
+            opponent_runs().map(burracos).sum::<usize>() as f64,
+            view.open_pile.len() as f64,
+            view.draw_pile_size as f64,
+            view.pots_left as f64,
+            view.team.has_reached_pot as u8 as f64,
+            view.opponents.iter().any(|o| o.has_reached_pot) as u8 as f64,
+            view.opponents
+                .iter()
+                .flat_map(|o| o.hand_sizes.iter().map(|(_p, size)| *size))
+                .min()
+                .unwrap_or(0) as f64,
+            (table_score(team_runs) - best_opponent_score) as f64,
+        ];
+        FeatureExtractor {
+            view,
+            state_features,
+        }
+    }
+
+    pub fn extract(&self, action: &Action) -> Features {
+        let scoring = self.view.rules.run.scoring;
+        let team_runs = self.view.team.played_runs;
+        let mut features = [0.0; NUM_FEATURES];
+        features[..NUM_STATE_FEATURES].copy_from_slice(&self.state_features);
+
+        let kind = match action {
+            Action::Draw(DrawAction::DrawOpen) => 0,
+            Action::Draw(DrawAction::DrawPile) => 1,
+            Action::Play(PlayAction::StartRun(_)) => 2,
+            Action::Play(PlayAction::AppendTop(_, _) | PlayAction::AppendBottom(_, _)) => 3,
+            Action::Play(PlayAction::ReplaceWildcard(_, _, _)) => 4,
+            Action::Play(PlayAction::MoveCard(_, _, _)) => 5,
+            Action::Play(PlayAction::Noop) => 6,
+            Action::Discard(_) => 7,
+        };
+        features[ACTION_KIND + kind] = 1.0;
+
+        let cards = match action {
+            Action::Draw(DrawAction::DrawOpen) => Cards(self.open_pile_draw().to_vec()),
+            Action::Draw(DrawAction::DrawPile) => Cards::default(),
+            Action::Play(play) => play.cards_played(),
+            Action::Discard(discard) => Cards(vec![discard.0]),
+        };
+        features[ACTION_CARDS] = match action {
+            Action::Draw(DrawAction::DrawPile) => 1.0,
+            _ => cards.len() as f64,
+        };
+        features[ACTION_WILDCARDS] = cards.iter().filter(|c| c.is_wildcard()).count() as f64;
+        features[ACTION_VALUE] = cards.value_sum_with(scoring) as f64;
+
+        let run_after = match action {
+            Action::Play(PlayAction::StartRun(run)) => Some(run.clone()),
+            Action::Play(PlayAction::AppendTop(i, cards)) => team_runs
+                .get(*i)
+                .and_then(|r| r.append(cards, Append::Top).ok()),
+            Action::Play(PlayAction::AppendBottom(i, cards)) => team_runs
+                .get(*i)
+                .and_then(|r| r.append(cards, Append::Bottom).ok()),
+            Action::Play(PlayAction::ReplaceWildcard(i, at, card)) => team_runs
+                .get(*i)
+                .and_then(|r| r.replace_wildcard(*at, card).ok()),
+            Action::Play(PlayAction::MoveCard(i, from, to)) => {
+                team_runs.get(*i).and_then(|r| r.move_card(*from, *to).ok())
+            }
+            _ => None,
+        };
+        if let Some(run) = run_after {
+            features[RUN_LENGTH_AFTER] = run.cards().len() as f64;
+            features[RUN_CLEAN_AFTER] = run.is_clean() as u8 as f64;
+            features[RUN_BURRACO_AFTER] = run.is_burraco() as u8 as f64;
+        }
+        if let Action::Discard(discard) = action {
+            features[DISCARD_FITS_OPPONENT] = self.fits_opponent(discard.0) as u8 as f64;
+        }
+        features[BIAS] = 1.0;
+        features
+    }
+
+    /// As `BurracoState::open_pile_draw`, from the view
+    fn open_pile_draw(&self) -> &[Card] {
+        let open_pile = self.view.open_pile;
+        match self.view.rules.open_pile_draw {
+            OpenPileDraw::TopCardOnly => &open_pile[open_pile.len().saturating_sub(1)..],
+            _ => open_pile,
+        }
+    }
+
+    fn fits_opponent(&self, card: Card) -> bool {
+        let cards = Cards(vec![card]);
+        self.view
+            .opponents
+            .iter()
+            .flat_map(|o| o.played_runs.iter())
+            .any(|r| {
+                r.append(&cards, Append::Top).is_ok() || r.append(&cards, Append::Bottom).is_ok()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::DiscardAction;
+    use crate::model::Rank::*;
+    use crate::model::Suit::*;
+    use crate::rules::Rules;
+    use crate::scenario::ScenarioBuilder;
+
+    fn feature(features: &Features, name: &str) -> f64 {
+        let i = FEATURE_NAMES.iter().position(|n| *n == name).expect(name);
+        features[i]
+    }
+
+    #[test]
+    fn test_extract() -> Result<(), String> {
+        let state = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♣4, ♣5, ♣6, ♥9, JK")
+            .run(0, "♠3, ♠4, ♠5, ♠6, ♠7, ♠8")
+            .run(1, "♦8, ♦9, ♦10")
+            .open_pile("♥K, ♥Q")
+            .build_state()?;
+        let view = state.player_view(0);
+        let extractor = FeatureExtractor::new(&view);
+
+        let append = Action::Play(PlayAction::AppendTop(0, Cards::of("♠9")?));
+        let features = extractor.extract(&append);
+        assert_eq!(NUM_FEATURES, FEATURE_NAMES.len());
+        let named = [
+            (ACTION_KIND, "draw_open"),
+            (ACTION_CARDS, "action_cards"),
+            (ACTION_WILDCARDS, "action_wildcards"),
+            (ACTION_VALUE, "action_value"),
+            (RUN_LENGTH_AFTER, "run_length_after"),
+            (RUN_CLEAN_AFTER, "run_clean_after"),
+            (RUN_BURRACO_AFTER, "run_burraco_after"),
+            (DISCARD_FITS_OPPONENT, "discard_fits_opponent"),
+            (BIAS, "bias"),
+        ];
+        for (i, name) in named.iter() {
+            assert_eq!(*name, FEATURE_NAMES[*i]);
+        }
+        assert_eq!(3.0, feature(&features, "hand_clubs"));
+        assert_eq!(1.0, feature(&features, "hand_wildcards"));
+        assert_eq!(5.0, feature(&features, "hand_size"));
+        assert_eq!(6.0, feature(&features, "team_longest_run"));
+        assert_eq!(3.0, feature(&features, "opponent_longest_run"));
+        assert_eq!(2.0, feature(&features, "open_pile_size"));
+        assert_eq!(1.0, feature(&features, "append"));
+        assert_eq!(7.0, feature(&features, "run_length_after"));
+        assert_eq!(1.0, feature(&features, "run_burraco_after"));
+        assert_eq!(1.0, feature(&features, "bias"));
+
+        let draw = extractor.extract(&Action::Draw(DrawAction::DrawOpen));
+        assert_eq!(2.0, feature(&draw, "action_cards"));
+        assert_eq!(0.0, feature(&draw, "append"));
+
+        let discard = extractor.extract(&Action::Discard(DiscardAction(Card(Diamonds, Jack))));
+        assert_eq!(1.0, feature(&discard, "discard_fits_opponent"));
+        assert_eq!(10.0, feature(&discard, "action_value"));
+        Ok(())
+    }
+}
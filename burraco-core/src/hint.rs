@@ -0,0 +1,263 @@
+use std::iter;
+
+use rand::prelude::*;
+
+use crate::actions::Action;
+use crate::actions::BurracoGame;
+use crate::actions::GamePhase;
+use crate::agent::create_agent;
+use crate::agent::AgentType;
+use crate::agent::Alternative;
+use crate::agent::Explanation;
+use crate::model::BurracoState;
+use crate::model::Card;
+use crate::model::Cards;
+use crate::model::PlayerView;
+use crate::scenario::seat_cards;
+
+/// A move recommended to a human player
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub action: Action,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct HintConfig {
+    /// the stronger the agent, the better the hints, the expert gives the clearest reasons
+    pub agent: AgentType,
+    pub max_hints: usize,
+    /// for dealing the cards the player cannot see, see `sample_state`
+    pub seed: u64,
+}
+
+impl Default for HintConfig {
+    fn default() -> Self {
+        HintConfig {
+            agent: AgentType::Expert,
+            max_hints: 3,
+            seed: 0,
+        }
+    }
+}
+
+/// The best moves for the player of the view in the phase, best first, as the agent of the
+/// config ranks them in its explanation, see `BurracoAgent::explain_play`.
+///
+/// Only what the player knows goes into the hints. An agent that does not explain itself gives
+/// a single hint, its own choice.
+pub fn hints(
+    view: &PlayerView,
+    phase: GamePhase,
+    config: &HintConfig,
+) -> Result<Vec<Hint>, String> {
+    if view.player != view.player_turn {
+        return Err(format!("Not the turn of player {}", view.player));
+    }
+    if let AgentType::Manual | AgentType::ManualCli = config.agent {
+        return Err("A manual agent gives no hints".into());
+    }
+    let state = sample_state(view, config.seed)?;
+    let game = BurracoGame::at_phase(state, phase);
+    let state = game.state();
+    let mut agent = create_agent(config.agent.clone());
+
+    let explanation = match phase {
+        GamePhase::Draw => agent.explain_draw(state),
+        GamePhase::Play => agent.explain_play(&game.play_actions(0), state),
+        GamePhase::Discard => agent.explain_discard(&game.discard_choices(), state),
+        GamePhase::Finished(_) => return Err("No moves once the game is finished".into()),
+    };
+    let explanation = match explanation {
+        Some(explanation) => explanation,
+        None => {
+            let action = match phase {
+                GamePhase::Draw => Action::Draw(agent.select_draw_action(state)),
+                GamePhase::Play => {
+                    Action::Play(agent.select_play_action(game.play_actions(0), state))
+                }
+                _ => Action::Discard(agent.select_discard_action(&game.discard_choices(), state)),
+            };
+            Explanation {
+                reason: format!("the choice of the {}", agent.display().to_lowercase()),
+                ranked: vec![Alternative::new(action, 0.0, "")],
+            }
+        }
+    };
+
+    let best_score = explanation.ranked.first().map(|a| a.score);
+    Ok(explanation
+        .ranked
+        .iter()
+        .take(config.max_hints)
+        .enumerate()
+        .map(|(i, alternative)| {
+            // the reason of the choice for the best, what the others are worth for the rest
+            let reason = match (i, best_score) {
+                (0, _) => explanation.reason.clone(),
+                _ if !alternative.note.is_empty() => alternative.note.clone(),
+                (_, None) => explanation.reason.clone(),
+                (_, Some(best_score)) => format!(
+                    "scores {:.2} against {:.2} for the best",
+                    alternative.score, best_score
+                ),
+            };
+            Hint {
+                action: alternative.action.clone(),
+                reason,
+            }
+        })
+        .collect())
+}
+
+/// A state the player of the view could be in: what the player knows is kept, the cards the
+/// player cannot see are dealt at random to the other hands, the pots and the draw pile
+pub fn sample_state(view: &PlayerView, seed: u64) -> Result<BurracoState, String> {
+    let rules = view.rules;
+    let teams: Vec<_> = iter::once(&view.team)
+        .chain(view.opponents.iter())
+        .collect();
+
+    let mut unseen = Cards::build_decks(&rules.deck);
+    let mut seen = |cards: &[Card]| -> Result<(), String> {
+        for card in cards.iter() {
+            let index = unseen
+                .iter()
+                .position(|c| c == card)
+                .ok_or_else(|| format!("More {} seen than the decks have", card))?;
+            unseen.remove(index);
+        }
+        Ok(())
+    };
+    seen(view.hand)?;
+    seen(view.open_pile)?;
+    for team in teams.iter() {
+        for run in team.played_runs.iter() {
+            seen(run.cards())?;
+        }
+    }
+    unseen.shuffle(&mut StdRng::seed_from_u64(seed));
+
+    let num_players = rules.table.num_players();
+    let mut hands = vec![None; num_players];
+    hands[view.player] = Some(view.hand.clone());
+    let mut hand_sizes = vec![0; num_players];
+    for (seat, hand_size) in teams.iter().flat_map(|team| team.hand_sizes.iter()) {
+        hand_sizes[*seat] = *hand_size;
+    }
+    // the pots are taken in order
+    let num_pots = rules.table.pot_sizes().len();
+    let taken = num_pots - view.pots_left;
+    let pots = (0..num_pots)
+        .map(|i| {
+            if i < taken {
+                Some(Cards::default())
+            } else {
+                None
+            }
+        })
+        .collect();
+    let mut state = seat_cards(
+        rules,
+        view.player_turn,
+        hands,
+        &hand_sizes,
+        pots,
+        Some(view.open_pile.clone()),
+        unseen,
+    )?;
+    for team in teams.iter() {
+        let state_team = &mut state.teams[team.team];
+        state_team.played_runs = team.played_runs.to_vec();
+        state_team.has_reached_pot = team.has_reached_pot;
+    }
+    if state.draw_pile.len() != view.draw_pile_size {
+        return Err(format!(
+            "{} cards unseen for a draw pile of {}",
+            state.draw_pile.len(),
+            view.draw_pile_size
+        ));
+    }
+
+    state.round = view.round;
+    state.must_use = view.must_use;
+    state.picked_single = view.picked_single;
+    state.close_answer = view.close_answer;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::DiscardAction;
+    use crate::actions::DrawAction;
+    use crate::model::Rank::*;
+    use crate::model::Suit::*;
+    use crate::rules::Rules;
+    use crate::scenario::ScenarioBuilder;
+
+    #[test]
+    fn test_sample_state() -> Result<(), String> {
+        let mut game = BurracoGame::from(BurracoState::init_seeded(2, 2, Some(3))?);
+        game.draw(DrawAction::DrawOpen)?;
+        let state = game.state();
+        let view = state.player_view(state.player_turn);
+
+        let sampled = sample_state(&view, 7)?;
+        assert_eq!(Ok(()), sampled.check_cards());
+        assert_eq!(state.must_use, sampled.must_use);
+        assert_eq!(state.open_pile, sampled.open_pile);
+        assert_eq!(state.draw_pile.len(), sampled.draw_pile.len());
+        for (seat, (t, p)) in state.player_team_idxs.iter().enumerate() {
+            let hand = &state.teams[*t].players[*p].hand;
+            let sampled_hand = &sampled.teams[*t].players[*p].hand;
+            assert_eq!(hand.len(), sampled_hand.len());
+            if seat == state.player_turn {
+                assert_eq!(hand, sampled_hand);
+            }
+        }
+        let sampled_view = sampled.player_view(state.player_turn);
+        assert_eq!(format!("{:?}", view), format!("{:?}", sampled_view));
+        Ok(())
+    }
+
+    #[test]
+    fn test_hints() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♣10, ♥K, ♥Q")
+            .run(1, "♣5, ♣6, ♣7, ♣8, ♣9")
+            .phase(GamePhase::Discard)
+            .build()?;
+        let view = game.state().player_view(0);
+        let config = HintConfig {
+            max_hints: 2,
+            ..HintConfig::default()
+        };
+        assert_eq!(
+            vec![
+                Hint {
+                    action: Action::Discard(DiscardAction(Card(Hearts, Queen))),
+                    reason: "every card costs something, this one the least: \
+                             parts it from ♥K, which it could run with"
+                        .into(),
+                },
+                Hint {
+                    action: Action::Discard(DiscardAction(Card(Hearts, King))),
+                    reason: "parts it from ♥Q, which it could run with".into(),
+                },
+            ],
+            hints(&view, GamePhase::Discard, &config)?
+        );
+
+        let dumb = HintConfig {
+            agent: AgentType::Dumb,
+            ..config
+        };
+        let play = hints(&view, GamePhase::Play, &dumb)?;
+        assert_eq!(1, play.len());
+        assert_eq!("the choice of the dumb agent", play[0].reason);
+        assert!(hints(&view, GamePhase::Finished(0), &config).is_err());
+        assert!(hints(&game.state().player_view(1), GamePhase::Discard, &config).is_err());
+        Ok(())
+    }
+}
@@ -1,4 +1,17 @@
 pub mod actions;
 pub mod agent;
+pub mod budget;
 pub mod cli_display;
+pub mod config;
+pub mod difficulty;
+pub mod error;
+pub mod evaluate;
+pub mod features;
+pub mod hint;
 pub mod model;
+pub mod policy;
+pub mod rules;
+pub mod scenario;
+pub mod tournament;
+pub mod tuning;
+pub mod weights;
@@ -5,7 +5,14 @@ use burraco::model::BurracoState;
 use burraco::actions::BurracoGame;
 use burraco::agent::BurracoAgent;
 use burraco::agent::*;
-use burraco::cli_display::print_play_actions;
+use burraco::budget::{decide_discard, decide_draw, decide_play, Budget};
+use burraco::cli_display::{print_explanation, print_play_actions};
+use burraco::tournament::offer_close_query;
+
+/// Wall time for an agent to decide, after which it makes a default action
+const DECISION_MILLIS: f64 = 2000.0;
+/// Of the alternatives an agent explains a decision with, the best ones shown
+const EXPLAINED_ALTERNATIVES: usize = 5;
 
 fn main() -> Result<(), String> {
     use burraco::actions::GamePhase::*;
@@ -14,15 +21,13 @@ fn main() -> Result<(), String> {
     let version = option_env!("PROJECT_VERSION").unwrap_or(env!("CARGO_PKG_VERSION"));
     println!("This binary was built from {}", version);
 
-    let state = BurracoState::init_with(2, 2);
+    let state = BurracoState::init_with(2, 2)?;
 
     let mut game = BurracoGame::from(state);
     println!("GAME START");
     println!("{}", game);
     println!("---");
 
-    let orig_cards = game.state().cards_total();
-
     let mut agents: [Box<dyn BurracoAgent>; 4] = [
         Box::new(ManualCliAgent {}),
         // Box::new(MaxAgent {}),
@@ -41,18 +46,22 @@ fn main() -> Result<(), String> {
     // let mut agent = ManualCliAgent {};
 
     'round: loop {
-        if game.state().cards_total() > orig_cards {
-            panic!(
-                "Cards are procreating! {} vs orig {}",
-                game.state().cards_total(),
-                orig_cards
-            );
-        }
         let agent = &mut agents[game.state().player_turn];
-        let draw_action = agent.select_draw_action(game.state());
+        let draw = decide_draw(
+            agent.as_mut(),
+            game.state(),
+            &mut Budget::time(DECISION_MILLIS),
+        );
+        let draw_action = draw.action;
 
         println!("Agent: {}", agent.display());
+        if draw.fell_back {
+            println!("Out of time, drawing by default");
+        }
         println!("Draw action: {}", &draw_action);
+        if let Some(explanation) = &draw.explanation {
+            print_explanation(explanation, EXPLAINED_ALTERNATIVES);
+        }
         game.draw(draw_action)?;
         if let Finished(_) = game.phase() {
             println!("OUT OF PILE CARDS!");
@@ -61,33 +70,39 @@ fn main() -> Result<(), String> {
         println!("---");
         println!("{}", game);
 
+        if let Some(may_close) = offer_close_query(&mut game, &mut agents)? {
+            let answer = if may_close { "yes" } else { "no" };
+            println!("Asked partner: may I close? The answer is {}", answer);
+        }
+        let agent = &mut agents[game.state().player_turn];
+
         // probably enough even if new runs are created?
         let mut moves_allowed = game.current_team().played_runs.len();
 
         // play until noop
         'player_plays: loop {
-            if game.state().cards_total() > orig_cards {
-                panic!(
-                    "Cards are procreating! {} vs orig {}",
-                    game.state().cards_total(),
-                    orig_cards
-                );
-            }
-
-            let available_actions = PlayAction::enumerate(
-                &game.current_team().played_runs,
-                &game.current_player().hand,
-                moves_allowed,
-            );
+            let available_actions = game.play_actions(moves_allowed);
             print_play_actions(&available_actions, &game.current_team().played_runs);
-            let selected_action = agent.select_play_action(available_actions, game.state());
+            let play = decide_play(
+                agent.as_mut(),
+                available_actions,
+                game.state(),
+                &mut Budget::time(DECISION_MILLIS),
+            );
+            let selected_action = play.action;
             if let PlayAction::MoveCard(_, _, _) = selected_action {
                 moves_allowed -= 1;
             }
 
             println!("---");
             println!("Agent: {}", agent.display());
+            if play.fell_back {
+                println!("Out of time, playing by default");
+            }
             println!("Playing action: {}", selected_action);
+            if let Some(explanation) = &play.explanation {
+                print_explanation(explanation, EXPLAINED_ALTERNATIVES);
+            }
             game.play(selected_action)?;
             if let Finished(_) = game.phase() {
                 let player_turn = game.state().player_turn;
@@ -105,9 +120,22 @@ fn main() -> Result<(), String> {
             }
         }
 
-        let discard_action = agent.select_discard_action(&game.current_player().hand, game.state());
+        let discard_choices = game.discard_choices();
+        let discard = decide_discard(
+            agent.as_mut(),
+            &discard_choices,
+            game.state(),
+            &mut Budget::time(DECISION_MILLIS),
+        );
+        let discard_action = discard.action;
         println!("Agent: {}", agent.display());
+        if discard.fell_back {
+            println!("Out of time, discarding by default");
+        }
         println!("Discard action: {}", discard_action);
+        if let Some(explanation) = &discard.explanation {
+            print_explanation(explanation, EXPLAINED_ALTERNATIVES);
+        }
         game.discard(discard_action)?;
         if let Finished(_) = game.phase() {
             let player_turn = game.state().player_turn;
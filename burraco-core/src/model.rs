@@ -2,6 +2,14 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 use std::str;
 
+use crate::rules::DealStyle;
+use crate::rules::DeckConfig;
+use crate::rules::OpenPileDraw;
+use crate::rules::Rules;
+use crate::rules::RunRules;
+use crate::rules::Scoring;
+use crate::rules::TableVariant;
+
 use Rank::*;
 use Suit::*;
 
@@ -78,6 +86,7 @@ impl Rank {
             _ => panic!("unsupported index mapping"), // assume we don't need to handle joker here
         }
     }
+    /// Cyclic next rank, see `AcePolicy` for where sequences may place the Ace
     pub fn next(&self) -> Rank {
         match self {
             Ace => Two,
@@ -99,7 +108,26 @@ impl Rank {
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Card(pub Suit, pub Rank);
 
+/// Copies of each card, indexed by `Card::deck_index`
+pub type CardCounts = [usize; 53];
+
 impl Card {
+    /// Position of the card in a single deck from `Cards::build_deck`, the Joker last
+    pub fn deck_index(&self) -> usize {
+        let suit_index = match self.0 {
+            Clubs => 0,
+            Diamonds => 1,
+            Hearts => 2,
+            Spades => 3,
+            Jokers => return 52,
+        };
+        suit_index * 13 + (self.1.index() as usize - 2)
+    }
+
+    pub fn is_wildcard(&self) -> bool {
+        self.1 == Joker || self.1 == Two
+    }
+
     fn parse(string: &str) -> Result<Card, String> {
         if string == "JK" {
             return Ok(Card(Jokers, Joker));
@@ -145,11 +173,12 @@ impl Card {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
 pub struct Cards(pub Vec<Card>);
 
 impl Cards {
-    fn build_deck(num_jokers: usize) -> Cards {
+    /// A single deck of 52 cards and the jokers, unshuffled
+    pub fn build_deck(num_jokers: usize) -> Cards {
         let mut deck = Vec::new();
 
         for suit in SUITS.iter() {
@@ -165,6 +194,44 @@ impl Cards {
         Cards(deck)
     }
 
+    /// All the decks of the config, unshuffled
+    pub fn build_decks(config: &DeckConfig) -> Cards {
+        let mut cards = Cards::default();
+        for _i in 0..config.num_decks {
+            cards.append(&mut Cards::build_deck(config.jokers_per_deck));
+        }
+        cards
+    }
+
+    /// Number of copies of each card
+    pub fn deck_counts(&self) -> CardCounts {
+        let mut counts = [0; 53];
+        for card in self.iter() {
+            counts[card.deck_index()] += 1;
+        }
+        counts
+    }
+
+    /// The cards with different counts, as "card actual of expected"
+    pub fn count_mismatches(expected: &CardCounts, actual: &CardCounts) -> Vec<String> {
+        let deck = Cards::build_deck(1);
+        (0..expected.len())
+            .filter(|i| expected[*i] != actual[*i])
+            .map(|i| format!("{} {} of {}", deck[i], actual[i], expected[i]))
+            .collect()
+    }
+
+    /// Check that the cards are exactly those of the decks, in any order
+    pub fn validate_deck(&self, config: &DeckConfig) -> Result<(), String> {
+        let expected = Cards::build_decks(config).deck_counts();
+        let mismatches = Cards::count_mismatches(&expected, &self.deck_counts());
+        if mismatches.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Not a full deck: {}", mismatches.join(", ")))
+        }
+    }
+
     pub fn of(expr: &str) -> Result<Cards, String> {
         if expr.trim().is_empty() {
             return Ok(Cards(vec![]));
@@ -176,6 +243,11 @@ impl Cards {
         Ok(Cards(cards?))
     }
 
+    /// Moves the top `at` cards to the bottom, the top of a deck is the back
+    pub fn cut(&mut self, at: usize) {
+        self.rotate_right(at)
+    }
+
     pub fn drain_back(&mut self, num_cards: usize) -> Cards {
         let index = self.len() - num_cards;
         Cards(self.split_off(index))
@@ -186,7 +258,11 @@ impl Cards {
     }
 
     pub fn value_sum(&self) -> i32 {
-        self.iter().map(|c| c.1.value()).sum()
+        self.value_sum_with(Scoring::Italian)
+    }
+
+    pub fn value_sum_with(&self, scoring: Scoring) -> i32 {
+        self.iter().map(|c| scoring.card_value(c.1)).sum()
     }
 }
 
@@ -215,6 +291,14 @@ pub struct Team {
     pub played_runs: Vec<Run>,
     pub has_reached_pot: bool,
     pub has_used_pot: bool,
+    /// points deducted for breaking the discard rules
+    pub penalties: i32,
+}
+
+impl Team {
+    pub fn has_burraco(&self) -> bool {
+        self.played_runs.iter().any(|r| r.is_burraco())
+    }
 }
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum RunType {
@@ -222,10 +306,21 @@ pub enum RunType {
     Group,
 }
 
+/// How the cards of a sequence run map to consecutive ranks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceSlots {
+    /// slot of the first card, Ace is 1 when used low and 14 when used high
+    pub low: i16,
+    /// index of the card used as wildcard
+    pub wildcard_at: Option<usize>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Run {
     cards: Cards,
     run_type: RunType,
+    /// the rules the run was built with, and keeps being validated with
+    rules: RunRules,
 }
 
 // ensure append only?
@@ -238,77 +333,46 @@ impl Run {
         self.run_type
     }
 
+    pub fn rules(&self) -> RunRules {
+        self.rules
+    }
+
     pub fn is_burraco(&self) -> bool {
         self.cards.len() >= 7
     }
 
+    /// Without a wildcard, a Two in its natural place of a sequence does not count
+    pub fn is_clean(&self) -> bool {
+        match self.run_type {
+            RunType::Sequence => Run::sequence_slots_with(&self.cards, self.rules)
+                .map(|slots| slots.wildcard_at.is_none())
+                .expect("validated when built"),
+            RunType::Group => self.cards.iter().all(|c| !c.is_wildcard()),
+        }
+    }
+
     pub fn burraco_value(&self) -> i32 {
         if self.is_burraco() {
             match self.run_type {
-                RunType::Sequence => {
-                    let mut num_clean_in_sequence = 0;
-                    let mut max_num_clean_in_sequence = 0;
-
-                    let mut last_was_clean = false;
-
-                    for i in 1..self.cards.len() {
-                        let prev_card = self.cards[i - 1];
-                        let card = self.cards[i];
-
-                        let mut clean_transition = false;
-
-                        if prev_card.0 == card.0 {
-                            if prev_card.1 == Two {
-                                if card.1 == Numerical(3) {
-                                    // Two used cleanly as two in sequence
-                                    clean_transition = true
-                                }
-                            } else {
-                                clean_transition = true;
-                            }
-                        }
-
-                        if i == self.cards.len() - 1 {
-                            // last card
-
-                            if card.1 == Two {
-                                // cover dirty case not validated by above prev clean two check
-                                clean_transition = false;
-                            }
-
-                            if last_was_clean && clean_transition {
-                                // count up for this card
-                                num_clean_in_sequence += 1;
-                            }
-                        }
-
-                        if clean_transition {
-                            // count up for prev card
-                            num_clean_in_sequence += 1;
-                        } else {
-                            if last_was_clean {
-                                num_clean_in_sequence += 1; // max should include this card
-                            }
-                            max_num_clean_in_sequence =
-                                max_num_clean_in_sequence.max(num_clean_in_sequence);
-                            num_clean_in_sequence = 0;
-                        }
-
-                        last_was_clean = clean_transition;
+                RunType::Sequence if self.rules.scoring == Scoring::Brazilian => {
+                    let slots = Run::sequence_slots_with(&self.cards, self.rules)
+                        .expect("validated when built");
+                    match slots.wildcard_at {
+                        None => 200,
+                        Some(_) => 100,
                     }
-                    if num_clean_in_sequence == self.cards.len() {
-                        if num_clean_in_sequence == 13 {
-                            300
-                        } else {
-                            200
-                        }
-                    } else if max_num_clean_in_sequence >= 7
-                        && max_num_clean_in_sequence == self.cards.len() - 1
-                    {
+                }
+                RunType::Sequence => {
+                    let slots = Run::sequence_slots_with(&self.cards, self.rules)
+                        .expect("validated when built");
+                    let last = self.cards.len() - 1;
+                    match slots.wildcard_at {
+                        // every rank from Two to Ace, with the Ace where the policy allows it
+                        None if self.cards.len() >= 13 => 300,
+                        None => 200,
                         // has to be a wildcard in either end
-                        150
-                    } else {
-                        100
+                        Some(i) if (i == 0 || i == last) && last >= 7 => 150,
+                        Some(_) => 100,
                     }
                 }
                 RunType::Group => {
@@ -326,124 +390,136 @@ impl Run {
         burraco_score + cards_score
     }
     pub fn score_burraco_cards(&self) -> (i32, i32) {
-        let cards_score: i32 = self.cards.value_sum();
+        let cards_score: i32 = self.cards.value_sum_with(self.rules.scoring);
 
         let burraco_score = self.burraco_value();
         // TODO: burraco_score
         (burraco_score, cards_score)
     }
 
+    /// Build a sequence run with the default rules
     pub fn build_sequence_run(cards: Cards) -> Result<Run, String> {
+        Run::build_sequence_run_with(cards, RunRules::default())
+    }
+
+    pub fn build_sequence_run_with(cards: Cards, rules: RunRules) -> Result<Run, String> {
+        Run::sequence_slots_with(&cards, rules)?;
+
+        Ok(Run {
+            cards,
+            run_type: RunType::Sequence,
+            rules,
+        })
+    }
+
+    /// Find how the ordered cards map to consecutive slots of a sequence run.
+    ///
+    /// Every card has to be natural in its slot, except for at most one
+    /// wildcard (a Joker or any Two). A Two of the run suit in slot 2 is
+    /// natural, anywhere else it is the wildcard.
+    pub fn sequence_slots(cards: &Cards) -> Result<SequenceSlots, String> {
+        Run::sequence_slots_with(cards, RunRules::default())
+    }
+
+    pub fn sequence_slots_with(cards: &Cards, rules: RunRules) -> Result<SequenceSlots, String> {
         if cards.len() < 3 {
             return Err("Need at least 3 cards to create a sequence run".into());
         }
-        let first_known_suit = cards
-            .iter()
-            .find(|c| c.1 != Joker && c.1 != Two)
-            .ok_or_else(|| "Need at least some non wild cards for sequence run".to_string())?
-            .0;
-        let num_same_two = cards
+        let (anchor_i, anchor) = cards
             .iter()
-            .filter(|c| c.0 == first_known_suit && c.1 == Rank::Two)
-            .count();
-        let num_other_two = cards
-            .iter()
-            .filter(|c| c.0 != first_known_suit && c.1 == Rank::Two)
-            .count();
-        let num_joker = cards.iter().filter(|c| c.1 == Rank::Joker).count();
+            .enumerate()
+            .find(|(_, c)| c.1 != Joker && c.1 != Two)
+            .ok_or_else(|| "Need at least some non wild cards for sequence run".to_string())?;
+        let suit = anchor.0;
 
-        // two Two:s of main suit could be allowed, cap to 1 in this count
-        // NOTE: we assume two decks
-        if num_same_two.min(1) + num_other_two + num_joker > 2 {
-            return Err("Too many wildcards in sequence run".into());
+        if cards
+            .iter()
+            .any(|c| c.1 != Joker && c.1 != Two && c.0 != suit)
+        {
+            return Err("Mismatched suit in sequence run".into());
         }
 
-        let mut wildcard_replaces = None;
-
-        for i in 0..cards.len() {
-            let prev_card = if i > 0 { Some(cards[i - 1]) } else { None };
-            let card = cards[i];
-
-            let next_card = if i < cards.len() - 1 {
-                Some(cards[i + 1])
-            } else {
-                None
-            };
+        // the first non wild card is natural, which leaves one or two (Ace) options
+        let (min_slot, max_slot) = rules.ace_policy.slot_range();
+        let anchor_slots: Vec<i16> = match anchor.1 {
+            Ace => vec![1, 14],
+            rank => vec![rank.index()],
+        };
+        if cards.len() > rules.ace_policy.max_sequence_len() {
+            return Err(format!(
+                "Sequence cannot be longer than {} cards",
+                rules.ace_policy.max_sequence_len()
+            ));
+        }
 
-            let curr_wildcard_replacement = match (prev_card, card, next_card) {
-                // Two used as two, in same suit (REF1)
-                (_, Card(suit1, Two), Some(Card(suit2, Numerical(3))))
-                    if suit1 == first_known_suit && suit2 == first_known_suit =>
-                {
-                    None
-                }
-                // Special case for same suit Two as Ace
-                (None, Card(suit1, Two), Some(Card(suit2, Two)))
-                    if suit1 == first_known_suit && suit2 == first_known_suit =>
-                {
-                    Some((i, Ace))
-                }
-                // Two used as two with wildcard as three
-                (_, Card(suit, Two), Some(Card(_, Two | Joker))) if suit == first_known_suit => {
-                    None
-                }
-                // Two used as wildcard, in any other suit
-                (Some(Card(_, prev_rank)), Card(_, Joker | Two), _) if prev_rank != Joker => {
-                    Some((i, prev_rank.next()))
+        let mut best: Option<SequenceSlots> = None;
+        let mut first_error = None;
+        for anchor_slot in anchor_slots {
+            let low = anchor_slot - anchor_i as i16;
+            match Run::assign_slots(cards, suit, low, min_slot, max_slot) {
+                Ok(slots) => {
+                    if best.is_none_or(|b| b.wildcard_at.is_some() && slots.wildcard_at.is_none()) {
+                        best = Some(slots);
+                    }
                 }
-                (None, Card(_, Joker | Two), Some(Card(_, next_rank)))
-                    if next_rank.prev().is_some() =>
-                {
-                    Some((i, next_rank.prev().unwrap()))
+                Err(e) => {
+                    first_error.get_or_insert(e);
                 }
-                _ => None,
-            };
-
-            if curr_wildcard_replacement.is_some() {
-                if wildcard_replaces.is_some() {
-                    return Err("You have already used a wildcard in this run".into());
-                } else {
-                    wildcard_replaces = curr_wildcard_replacement;
-                }
-                let valid_wildcard_sequence = !matches!(
-                    (prev_card, card.1, next_card),
-                    (Some(Card(_, Ace)), _, None)
-                );
+            }
+        }
 
-                if !valid_wildcard_sequence {
-                    return Err("Cannot extend from Ace with wildcard".into());
-                }
-            } else {
-                if card.0 != first_known_suit {
-                    return Err("Mismatched suit in sequence run".into());
-                }
+        best.ok_or_else(|| first_error.expect("at least one anchor slot"))
+    }
 
-                if let Some(prev) = prev_card {
-                    let prev_rank = match wildcard_replaces {
-                        Some((prev_i, rank)) if prev_i == i - 1 => rank,
-                        _ => prev.1,
-                    };
+    fn assign_slots(
+        cards: &Cards,
+        suit: Suit,
+        low: i16,
+        min_slot: i16,
+        max_slot: i16,
+    ) -> Result<SequenceSlots, String> {
+        let high = low + cards.len() as i16 - 1;
+        if low < min_slot || high > max_slot {
+            return Err("Sequence cannot extend past Ace".into());
+        }
 
-                    let valid_rank_sequence = match (prev_rank, card.1, next_card) {
-                        (Ace, curr, _) => curr == Two,
-                        (prev_rank, Ace, next) => next.is_none() && prev_rank == King,
-                        _ => prev_rank.index() + 1 == card.1.index(),
-                    };
+        let mut wildcard_at = None;
+        for (i, card) in cards.iter().enumerate() {
+            let slot = low + i as i16;
+            let is_natural = card.0 == suit
+                && match card.1 {
+                    Ace => slot == 1 || slot == 14,
+                    Joker => false,
+                    rank => rank.index() == slot,
+                };
+            if is_natural {
+                continue;
+            }
 
-                    if !valid_rank_sequence {
-                        return Err(format!("Invalid sequence: {} to {}", prev, &card));
-                    }
-                }
+            if card.1 != Joker && card.1 != Two {
+                return Err(match i {
+                    0 => format!("Invalid sequence: {} first", card),
+                    _ => format!("Invalid sequence: {} to {}", cards[i - 1], card),
+                });
+            }
+            if wildcard_at.is_some() {
+                return Err("Too many wildcards in sequence run".into());
             }
+            wildcard_at = Some(i);
         }
 
-        Ok(Run {
-            cards,
-            run_type: RunType::Sequence,
-        })
+        Ok(SequenceSlots { low, wildcard_at })
     }
 
+    /// Build a group run with the default rules
     pub fn build_group_run(cards: Cards) -> Result<Run, String> {
+        Run::build_group_run_with(cards, RunRules::default())
+    }
+
+    pub fn build_group_run_with(cards: Cards, rules: RunRules) -> Result<Run, String> {
+        if !rules.allow_groups {
+            return Err("Group runs are not allowed".into());
+        }
         if cards.len() < 3 {
             return Err("Need at least 3 cards to create a group run".into());
         }
@@ -485,9 +561,158 @@ impl Run {
         Ok(Run {
             run_type: RunType::Group,
             cards,
+            rules,
         })
     }
 
+    /// Build a sequence or group run, depending on what the cards look like
+    pub fn try_build(cards: Cards) -> Result<Run, String> {
+        Run::try_build_with(cards, RunRules::default())
+    }
+
+    pub fn try_build_with(cards: Cards, rules: RunRules) -> Result<Run, String> {
+        let mut natural_ranks = cards
+            .iter()
+            .map(|c| c.1)
+            .filter(|r| *r != Joker && *r != Two);
+        let looks_like_group = match natural_ranks.next() {
+            Some(first) => natural_ranks.all(|r| r == first),
+            None => true,
+        };
+
+        if looks_like_group {
+            Run::build_group_run_with(cards.clone(), rules).or_else(|group_err| {
+                Run::build_sequence_run_with(cards, rules).map_err(|_seq_err| group_err)
+            })
+        } else {
+            Run::build_sequence_run_with(cards.clone(), rules).or_else(|seq_err| {
+                Run::build_group_run_with(cards, rules).map_err(|_group_err| seq_err)
+            })
+        }
+    }
+
+    /// All the ways the cards, taken in any order, can be laid down as a run.
+    ///
+    /// Runs where a Two of the run suit is used as a natural Two come first,
+    /// and a wildcard that can go in either end gives one run per end.
+    pub fn arrangements(cards: &Cards) -> Vec<Run> {
+        Run::arrangements_with(cards, RunRules::default())
+    }
+
+    pub fn arrangements_with(cards: &Cards, rules: RunRules) -> Vec<Run> {
+        let mut candidates: Vec<(usize, Run)> = Vec::new();
+
+        let mut suits: Vec<Suit> = Vec::new();
+        for card in cards.iter().filter(|c| c.1 != Joker) {
+            if !suits.contains(&card.0) {
+                suits.push(card.0);
+            }
+        }
+
+        for suit in suits {
+            let naturals: Vec<Card> = cards
+                .iter()
+                .filter(|c| c.1 != Joker && c.1 != Two)
+                .cloned()
+                .collect();
+            if naturals.iter().any(|c| c.0 != suit) {
+                continue;
+            }
+            let same_suit_twos: Vec<Card> = cards
+                .iter()
+                .filter(|c| c.0 == suit && c.1 == Two)
+                .cloned()
+                .collect();
+
+            for natural_two in [true, false].iter() {
+                let mut slotted = naturals.clone();
+                let mut wildcards: Vec<Card> = cards
+                    .iter()
+                    .filter(|c| c.1 == Joker || (c.1 == Two && c.0 != suit))
+                    .cloned()
+                    .collect();
+                let mut twos = same_suit_twos.clone();
+                if *natural_two {
+                    match twos.pop() {
+                        Some(two) => slotted.push(two),
+                        None => continue,
+                    }
+                }
+                wildcards.append(&mut twos);
+                if wildcards.len() > 1 || slotted.is_empty() {
+                    continue;
+                }
+
+                let num_aces = slotted.iter().filter(|c| c.1 == Ace).count();
+                for num_low_aces in 0..=num_aces {
+                    let mut low_aces_left = num_low_aces;
+                    let mut slots: Vec<(i16, Card)> = slotted
+                        .iter()
+                        .map(|c| match c.1 {
+                            Ace if low_aces_left > 0 => {
+                                low_aces_left -= 1;
+                                (1, *c)
+                            }
+                            rank => (rank.index(), *c),
+                        })
+                        .collect();
+                    slots.sort_by_key(|(pos, _)| *pos);
+                    if slots.windows(2).any(|w| w[0].0 == w[1].0) {
+                        continue;
+                    }
+
+                    let min = slots[0].0;
+                    let max = slots[slots.len() - 1].0;
+                    let gaps = (max - min + 1) as usize - slots.len();
+                    let wildcard_positions: Vec<Option<i16>> = match (wildcards.first(), gaps) {
+                        (None, 0) => vec![None],
+                        (Some(_), 1) => (min..=max)
+                            .find(|pos| slots.iter().all(|(p, _)| p != pos))
+                            .map(Some)
+                            .into_iter()
+                            .collect(),
+                        (Some(_), 0) => vec![min - 1, max + 1]
+                            .into_iter()
+                            .filter(|pos| (1..=14).contains(pos))
+                            .map(Some)
+                            .collect(),
+                        _ => vec![],
+                    };
+
+                    for wildcard_pos in wildcard_positions {
+                        let low = wildcard_pos.map_or(min, |pos| pos.min(min));
+                        let high = wildcard_pos.map_or(max, |pos| pos.max(max));
+                        let ordered: Vec<Card> = (low..=high)
+                            .map(|pos| match slots.iter().find(|(p, _)| *p == pos) {
+                                Some((_, card)) => *card,
+                                None => wildcards[0],
+                            })
+                            .collect();
+
+                        if let Ok(run) = Run::build_sequence_run_with(Cards(ordered), rules) {
+                            if candidates.iter().all(|(_, r)| *r != run) {
+                                candidates.push((wildcards.len(), run));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if let Ok(run) = Run::build_group_run_with(cards.clone(), rules) {
+            let num_wildcards = run
+                .cards()
+                .iter()
+                .filter(|c| c.1 == Joker || c.1 == Two)
+                .count()
+                .min(1);
+            candidates.push((num_wildcards, run));
+        }
+
+        candidates.sort_by_key(|(num_wildcards, _)| *num_wildcards);
+        candidates.into_iter().map(|(_, run)| run).collect()
+    }
+
     pub fn append(&self, cards: &Cards, append_to: Append) -> Result<Run, String> {
         let mut new_cards = self.cards.clone();
         match append_to {
@@ -501,8 +726,8 @@ impl Run {
         };
 
         match self.run_type {
-            RunType::Sequence => Run::build_sequence_run(new_cards),
-            RunType::Group => Run::build_group_run(new_cards),
+            RunType::Sequence => Run::build_sequence_run_with(new_cards, self.rules),
+            RunType::Group => Run::build_group_run_with(new_cards, self.rules),
         }
     }
 
@@ -515,15 +740,18 @@ impl Run {
         }
 
         let old_card = mem::replace(&mut new_cards[at], *card);
-        new_cards.insert(0, old_card);
-        let new_run = match self.run_type() {
-            RunType::Sequence => Run::build_sequence_run(new_cards)?,
+        if self.run_type() == RunType::Group {
             // we sort these, so we should still be able to get 150
-            RunType::Group => {
-                return Err("No point in replacing wildcard in group, use append".into())
-            }
-        };
-        Ok(new_run)
+            return Err("No point in replacing wildcard in group, use append".into());
+        }
+
+        // the replaced wildcard goes to the bottom if it fits there, else to the top
+        let mut bottom_cards = new_cards.clone();
+        bottom_cards.insert(0, old_card);
+        Run::build_sequence_run_with(bottom_cards, self.rules).or_else(|bottom_err| {
+            new_cards.push(old_card);
+            Run::build_sequence_run_with(new_cards, self.rules).map_err(|_top_err| bottom_err)
+        })
     }
 
     pub fn move_card(&self, from: usize, to: usize) -> Result<Run, String> {
@@ -542,7 +770,7 @@ impl Run {
         new_cards.remove(remove_idx);
 
         let new_run = match self.run_type() {
-            RunType::Sequence => Run::build_sequence_run(new_cards)?,
+            RunType::Sequence => Run::build_sequence_run_with(new_cards, self.rules)?,
             RunType::Group => return Err("No point in moving card in group".into()),
         };
         Ok(new_run)
@@ -556,21 +784,112 @@ pub enum Append {
 
 #[derive(Debug, Clone)]
 pub struct BurracoState {
-    pub num_teams: usize,
-    pub num_team_players: usize,
     pub draw_pile: Cards,
     pub open_pile: Cards,
-    pub pot1: Cards,
-    pub pot2: Cards,
+    /// dealt according to the table variant, a taken pot is left empty
+    pub pots: Vec<Cards>,
     pub teams: Vec<Team>,
+    /// as in `player_turn`, the player left of the dealer starts
+    pub dealer: usize,
     pub player_turn: usize,
     pub first_player: usize,
     /// (team_idx, in_team_idx)
     pub player_team_idxs: Vec<(usize, usize)>,
     pub round: u32,
+    /// card drawn from the open pile that has to be played before anything else
+    pub must_use: Option<Card>,
+    /// team of the player who ended the game by emptying their hand
+    pub went_out: Option<usize>,
+    /// the only card drawn from the open pile this turn
+    pub picked_single: Option<Card>,
+    /// the partner's answer to "may I close?" this turn
+    pub close_answer: Option<bool>,
+    pub rules: Rules,
+}
+
+/// The public side of a team, as seen from any player
+#[derive(Debug, Clone)]
+pub struct TeamView<'a> {
+    pub team: usize,
+    pub played_runs: &'a [Run],
+    /// (player, hand size) for the players of the team, player as in `player_turn`
+    pub hand_sizes: Vec<(usize, usize)>,
+    pub has_reached_pot: bool,
+}
+
+/// What a single player knows of the game, their own hand and the public side of the table
+#[derive(Debug, Clone)]
+pub struct PlayerView<'a> {
+    /// as in `player_turn`
+    pub player: usize,
+    pub hand: &'a Cards,
+    pub team: TeamView<'a>,
+    pub opponents: Vec<TeamView<'a>>,
+    pub open_pile: &'a Cards,
+    pub draw_pile_size: usize,
+    pub pots_left: usize,
+    pub player_turn: usize,
+    pub round: u32,
+    /// as in `BurracoState::must_use`, seen by all when it was drawn
+    pub must_use: Option<Card>,
+    /// as in `BurracoState::picked_single`
+    pub picked_single: Option<Card>,
+    /// as in `BurracoState::close_answer`, given out loud
+    pub close_answer: Option<bool>,
+    pub rules: &'a Rules,
+}
+
+impl<'a> PlayerView<'a> {
+    /// (player, hand size) for each partner of the player
+    pub fn partners(&self) -> Vec<(usize, usize)> {
+        self.team
+            .hand_sizes
+            .iter()
+            .filter(|(p, _)| *p != self.player)
+            .cloned()
+            .collect()
+    }
 }
 
 impl BurracoState {
+    pub fn team_view(&self, team: usize) -> TeamView<'_> {
+        TeamView {
+            team,
+            played_runs: &self.teams[team].played_runs,
+            hand_sizes: self
+                .player_team_idxs
+                .iter()
+                .enumerate()
+                .filter(|(_, (t, _))| *t == team)
+                .map(|(p, (t, tp))| (p, self.teams[*t].players[*tp].hand.len()))
+                .collect(),
+            has_reached_pot: self.teams[team].has_reached_pot,
+        }
+    }
+
+    /// The game as seen by a player, `player` as in `player_turn`
+    pub fn player_view(&self, player: usize) -> PlayerView<'_> {
+        let (team, team_player) = self.player_team_idxs[player];
+        PlayerView {
+            player,
+            hand: &self.teams[team].players[team_player].hand,
+            team: self.team_view(team),
+            opponents: (0..self.teams.len())
+                .filter(|t| *t != team)
+                .map(|t| self.team_view(t))
+                .collect(),
+            open_pile: &self.open_pile,
+            draw_pile_size: self.draw_pile.len(),
+            pots_left: self.pots.iter().filter(|p| !p.is_empty()).count(),
+            player_turn: self.player_turn,
+            round: self.round,
+            must_use: self.must_use,
+            picked_single: self.picked_single,
+            close_answer: self.close_answer,
+            rules: &self.rules,
+        }
+    }
+
     pub fn curr_team_player(&self) -> (usize, usize) {
         self.player_team_idxs[self.player_turn]
     }
@@ -580,80 +899,215 @@ impl BurracoState {
         team
     }
 
-    pub fn init_with(num_teams: usize, num_team_players: usize) -> BurracoState {
+    /// The team of the first player after the current one who is not a partner
+    pub fn next_opponent_team(&self) -> usize {
+        let curr_team = self.curr_team();
+        (1..self.player_team_idxs.len())
+            .map(|i| self.player_team_idxs[(self.player_turn + i) % self.player_team_idxs.len()].0)
+            .find(|team| *team != curr_team)
+            .expect("at least two teams")
+    }
+
+    pub fn init_with(num_teams: usize, num_team_players: usize) -> Result<BurracoState, String> {
         BurracoState::init_seeded(num_teams, num_team_players, None)
     }
 
+    /// An error if the number of teams and players is not a supported table variant
     pub fn init_seeded(
         num_teams: usize,
         num_team_players: usize,
         maybe_seed: Option<u64>,
-    ) -> BurracoState {
-        use rand::prelude::*;
+    ) -> Result<BurracoState, String> {
+        let table = TableVariant::from_counts(num_teams, num_team_players).ok_or_else(|| {
+            format!(
+                "Unsupported table: {} teams of {} players",
+                num_teams, num_team_players
+            )
+        })?;
+        let rules = Rules {
+            table,
+            ..Rules::default()
+        };
+        Ok(BurracoState::init_with_rules(rules, maybe_seed))
+    }
 
-        let mut seeded_rng = maybe_seed.map(StdRng::seed_from_u64);
+    /// Deals with a random dealer
+    pub fn init_with_rules(rules: Rules, maybe_seed: Option<u64>) -> BurracoState {
+        BurracoState::init_dealt(rules, None, maybe_seed)
+    }
 
-        let num_players = num_teams * num_team_players;
-        let starting_player = if let Some(seeded_rng) = &mut seeded_rng {
-            seeded_rng.gen_range(0..num_players)
-        } else {
-            thread_rng().gen_range(0..num_players)
+    /// The next hand, dealt by the player left of the dealer of this one
+    pub fn next_deal(&self, maybe_seed: Option<u64>) -> BurracoState {
+        let dealer = (self.dealer + 1) % self.player_team_idxs.len();
+        BurracoState::init_dealt(self.rules.clone(), Some(dealer), maybe_seed)
+    }
+
+    fn init_dealt(rules: Rules, dealer: Option<usize>, maybe_seed: Option<u64>) -> BurracoState {
+        use rand::prelude::*;
+
+        let mut rng = match maybe_seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
         };
 
-        // 2 decks
-        let mut deck = Cards::build_deck(3);
-        deck.append(&mut Cards::build_deck(3));
+        let num_players = rules.table.num_players();
+        let dealer = dealer.unwrap_or_else(|| {
+            let starting_player = rng.gen_range(0..num_players);
+            (starting_player + num_players - 1) % num_players
+        });
+
+        let mut deck = Cards::build_decks(&rules.deck);
+        deck.shuffle(&mut rng);
+        if rules.deal.cut {
+            let at = rng.gen_range(1..deck.len());
+            deck.cut(at);
+        }
+
+        BurracoState::deal(rules, dealer, deck)
+    }
 
-        if let Some(seeded_rng) = &mut seeded_rng {
-            deck.shuffle(seeded_rng)
+    /// Deals the deck as it is, e.g. to replay a recorded game, the top of the deck is the back
+    pub fn deal(rules: Rules, dealer: usize, deck: Cards) -> BurracoState {
+        let mut deck = deck;
+        let num_teams = rules.table.num_teams();
+        let num_team_players = rules.table.num_team_players();
+        let num_players = rules.table.num_players();
+        let hand_size = rules.table.hand_size();
+
+        let mut player_team_idxs = Vec::new();
+        for p in 0..num_team_players {
+            for t in 0..num_teams {
+                player_team_idxs.push((t, p))
+            }
+        }
+
+        let take_pots = |deck: &mut Cards| -> Vec<Cards> {
+            rules
+                .table
+                .pot_sizes()
+                .iter()
+                .map(|size| deck.drain_back(*size))
+                .collect()
+        };
+        let mut pots = if rules.deal.pots_first {
+            take_pots(&mut deck)
         } else {
-            deck.shuffle(&mut thread_rng())
+            Vec::new()
         };
 
-        let pot1 = deck.drain_back(11);
-        let pot2 = deck.drain_back(11);
+        let mut hands = vec![Cards::default(); num_players];
+        match rules.deal.style {
+            DealStyle::Packets => {
+                for t in 0..num_teams {
+                    for p in 0..num_team_players {
+                        let seat = player_team_idxs
+                            .iter()
+                            .position(|idxs| *idxs == (t, p))
+                            .expect("every player has a seat");
+                        hands[seat] = deck.drain_back(hand_size);
+                    }
+                }
+            }
+            DealStyle::Alternating => {
+                for _i in 0..hand_size {
+                    for s in 1..=num_players {
+                        let seat = (dealer + s) % num_players;
+                        hands[seat].append(&mut deck.drain_back(1));
+                    }
+                }
+            }
+        }
+
+        if !rules.deal.pots_first {
+            pots = take_pots(&mut deck);
+        }
 
         let mut teams = Vec::new();
         for _i in 0..num_teams {
-            let mut team_players = Vec::new();
-            for j in 0..num_team_players {
-                team_players.push(Player {
-                    hand: deck.drain_back(11),
-                });
-                team_players[j].hand.sort();
-            }
             teams.push(Team {
-                players: team_players,
+                players: Vec::new(),
                 has_reached_pot: false,
                 has_used_pot: false,
+                penalties: 0,
                 played_runs: Vec::new(),
             })
         }
-
-        let mut player_team_idxs = Vec::new();
-        for p in 0..num_team_players {
-            for t in 0..num_teams {
-                player_team_idxs.push((t, p))
-            }
+        // seats go around the table team by team, so players are added in team order
+        for (seat, hand) in hands.into_iter().enumerate() {
+            let (t, _p) = player_team_idxs[seat];
+            let mut hand = hand;
+            hand.sort();
+            teams[t].players.push(Player { hand });
         }
 
+        let starting_player = (dealer + 1) % num_players;
         let open_pile = deck.drain_back(1);
         let draw_pile = deck;
         BurracoState {
-            num_teams,
-            num_team_players,
             draw_pile,
             open_pile,
-            pot1,
-            pot2,
+            pots,
             teams,
+            dealer,
             player_turn: starting_player,
             first_player: starting_player,
             player_team_idxs,
             round: 0,
+            must_use: None,
+            went_out: None,
+            picked_single: None,
+            close_answer: None,
+            rules,
         }
     }
 
+    /// The cards drawing from the open pile would give the current player
+    pub fn open_pile_draw(&self) -> &[Card] {
+        match self.rules.open_pile_draw {
+            OpenPileDraw::WholePile | OpenPileDraw::WholePileMustUseTop => &self.open_pile,
+            OpenPileDraw::TopCardOnly => &self.open_pile[self.open_pile.len().saturating_sub(1)..],
+        }
+    }
+
+    /// Takes the first pot not yet taken, if any
+    pub fn take_pot(&mut self) -> Option<Cards> {
+        self.pots
+            .iter_mut()
+            .find(|p| !p.is_empty())
+            .map(std::mem::take)
+    }
+
+    /// All the cards of the game, wherever they are
+    pub fn all_cards(&self) -> Cards {
+        let mut cards = Cards::default();
+        for team in self.teams.iter() {
+            for run in team.played_runs.iter() {
+                cards.extend(run.cards().iter().cloned());
+            }
+            for player in team.players.iter() {
+                cards.extend(player.hand.iter().cloned());
+            }
+        }
+        for pot in self.pots.iter() {
+            cards.extend(pot.iter().cloned());
+        }
+        cards.extend(self.draw_pile.iter().cloned());
+        cards.extend(self.open_pile.iter().cloned());
+        cards
+    }
+
+    /// Check that no card has been lost or duplicated along the way
+    pub fn check_cards(&self) -> Result<(), String> {
+        if self.cards_total() != self.rules.deck.size() {
+            return Err(format!(
+                "{} cards in the game, the deck has {}",
+                self.cards_total(),
+                self.rules.deck.size()
+            ));
+        }
+        self.all_cards().validate_deck(&self.rules.deck)
+    }
+
     // for sanity checking
     pub fn cards_total(&self) -> usize {
         let team_cards: usize = self
@@ -666,8 +1120,8 @@ impl BurracoState {
             })
             .sum();
 
-        let pile_cards =
-            self.draw_pile.len() + self.open_pile.len() + self.pot1.len() + self.pot2.len();
+        let pot_cards: usize = self.pots.iter().map(|p| p.len()).sum();
+        let pile_cards = self.draw_pile.len() + self.open_pile.len() + pot_cards;
         team_cards + pile_cards
     }
 }
@@ -769,6 +1223,230 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_try_build() -> Result<(), String> {
+        assert_eq!(
+            RunType::Sequence,
+            Run::try_build(Cards::of("♥5,JK,♥7")?)?.run_type()
+        );
+        assert_eq!(
+            RunType::Group,
+            Run::try_build(Cards::of("♥7,♣2,♠7")?)?.run_type()
+        );
+        assert_eq!(
+            RunType::Group,
+            Run::try_build(Cards::of("♥2,♣2,♠2")?)?.run_type()
+        );
+        assert!(Run::try_build(Cards::of("♥7,♣8,♠7")?).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrangements_any_order() -> Result<(), String> {
+        let runs = Run::arrangements(&Cards::of("♥5,JK,♥7,♥4")?);
+        assert_eq!(
+            vec![Run::build_sequence_run(Cards::of("♥4,♥5,JK,♥7")?)?],
+            runs
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrangements_wildcard_either_end() -> Result<(), String> {
+        let runs = Run::arrangements(&Cards::of("♠6,JK,♠5")?);
+        assert_eq!(2, runs.len());
+        assert!(runs.contains(&Run::build_sequence_run(Cards::of("JK,♠5,♠6")?)?));
+        assert!(runs.contains(&Run::build_sequence_run(Cards::of("♠5,♠6,JK")?)?));
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrangements_prefer_natural_two() -> Result<(), String> {
+        let runs = Run::arrangements(&Cards::of("♣4,♣2,♣3")?);
+        assert_eq!(Run::build_sequence_run(Cards::of("♣2,♣3,♣4")?)?, runs[0]);
+        assert!(runs.contains(&Run::build_sequence_run(Cards::of("♣3,♣4,♣2")?)?));
+
+        let runs = Run::arrangements(&Cards::of("♣4,♣2,♣3,♣2")?);
+        assert_eq!(2, runs.len());
+        assert!(runs.contains(&Run::build_sequence_run(Cards::of("♣2,♣3,♣4,♣2")?)?));
+        assert!(runs.contains(&Run::build_sequence_run(Cards::of("♣2,♣2,♣3,♣4")?)?));
+
+        let runs = Run::arrangements(&Cards::of("♣3,♣2,♣A")?);
+        assert_eq!(vec![Run::build_sequence_run(Cards::of("♣A,♣2,♣3")?)?], runs);
+        Ok(())
+    }
+
+    #[test]
+    fn test_arrangements_group() -> Result<(), String> {
+        let runs = Run::arrangements(&Cards::of("♣9,♥2,♦9")?);
+        assert_eq!(vec![Run::build_group_run(Cards::of("♣9,♦9,♥2")?)?], runs);
+        assert!(Run::arrangements(&Cards::of("♣9,♥3,♦9")?).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sequence_slot_table() -> Result<(), String> {
+        // cards, (low slot, wildcard index) if valid
+        let table = vec![
+            ("♣A,♣2,♣3", Some((1, None))),
+            ("♣2,♣3,♣4", Some((2, None))),
+            ("♣2,♣2,♣3", Some((1, Some(0)))),
+            ("♣A,♣2,♣2", Some((1, Some(2)))),
+            ("♣2,♣3,♣2", Some((2, Some(2)))),
+            ("♣2,♣2,♣4", Some((2, Some(1)))),
+            ("♥2,♣3,♣4", Some((2, Some(0)))),
+            ("♣3,♣2,♣5", Some((3, Some(1)))),
+            ("JK,♣2,♣3", Some((1, Some(0)))),
+            ("♣2,♣3,♣4,JK", Some((2, Some(3)))),
+            ("♣2,JK,♣4", Some((2, Some(1)))),
+            ("♣Q,♣K,♣A", Some((12, None))),
+            ("JK,♣K,♣A", Some((12, Some(0)))),
+            ("♣J,♣2,♣K,♣A", Some((11, Some(1)))),
+            ("♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K", Some((1, None))),
+            ("♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K,♣A", Some((2, None))),
+            (
+                "♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K,♣A",
+                Some((1, None)),
+            ),
+            (
+                "♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,JK,♣A",
+                Some((1, Some(12))),
+            ),
+            ("♣K,♣A,JK", None),
+            ("♣K,♣A,♣2", None),
+            ("♣K,JK,♣2", None),
+            ("♣Q,♣K,JK,♣2", None),
+            ("♣A,♣2,♣2,♣3", None),
+            ("♣2,♣4,♣2,♣6", None),
+            ("♣2,♣3,JK,♣2", None),
+            ("♣3,♥2,♣5,JK", None),
+            ("♣3,♦4,♣5", None),
+            ("♣2,♥2,JK", None),
+        ];
+
+        for (cards, expected) in table {
+            let slots = Run::sequence_slots(&Cards::of(cards)?);
+            assert_eq!(
+                expected,
+                slots.as_ref().ok().map(|s| (s.low, s.wildcard_at)),
+                "{}: {:?}",
+                cards,
+                slots
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_burraco_score_table() -> Result<(), String> {
+        let table = vec![
+            ("♣A,♣2,♣3,♣4,♣5,♣6,♣7", 200),
+            ("♣2,♣2,♣3,♣4,♣5,♣6,♣7", 100),
+            ("♣2,♣2,♣3,♣4,♣5,♣6,♣7,♣8", 150),
+            ("♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣2", 150),
+            ("♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K,♣A", 300),
+            ("♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,JK,♣A", 100),
+            ("♣8,♣9,♣10,♣J,♣Q,♣K,♣A", 200),
+            ("JK,♣8,♣9,♣10,♣J,♣Q,♣K,♣A", 150),
+        ];
+
+        for (cards, expected) in table {
+            let run = Run::build_sequence_run(Cards::of(cards)?)?;
+            assert_eq!(expected, run.burraco_value(), "{}", cards);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_clean() -> Result<(), String> {
+        assert!(Run::build_sequence_run(Cards::of("♠2,♠3,♠4")?)?.is_clean());
+        assert!(!Run::build_sequence_run(Cards::of("♠3,♠4,♥2")?)?.is_clean());
+        assert!(Run::build_group_run(Cards::of("♠3,♥3,♦3")?)?.is_clean());
+        assert!(!Run::build_group_run(Cards::of("♠3,♥3,JK")?)?.is_clean());
+        Ok(())
+    }
+
+    #[test]
+    fn test_move_natural_two_to_wildcard() -> Result<(), String> {
+        let run = Run::build_sequence_run(Cards::of("♠2,♠3,♠4,♠5,♠6,♠7,♠8")?)?;
+        assert_eq!(200, run.burraco_value());
+
+        let moved = run.move_card(0, 7)?;
+        assert_eq!(Cards::of("♠3,♠4,♠5,♠6,♠7,♠8,♠2")?, *moved.cards());
+        assert_eq!(100, moved.burraco_value());
+
+        let moved_back = moved.move_card(6, 0)?;
+        assert_eq!(run, moved_back);
+        Ok(())
+    }
+
+    #[test]
+    fn test_replace_wildcard_to_top() -> Result<(), String> {
+        let run = Run::build_sequence_run(Cards::of("♠A,JK,♠3")?)?;
+        let replaced = run.replace_wildcard(1, &Card(Spades, Two))?;
+        assert_eq!(Cards::of("♠A,♠2,♠3,JK")?, *replaced.cards());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ace_policy_table() -> Result<(), String> {
+        use crate::rules::AcePolicy::*;
+
+        let table = vec![
+            // cards, valid with: LowOnly, HighOnly, BothEnds, NoWrap
+            ("♣A,♣2,♣3", [true, false, true, true]),
+            ("JK,♣2,♣3", [true, false, true, true]),
+            ("♣Q,♣K,♣A", [false, true, true, true]),
+            ("♣Q,♣K,JK", [false, true, true, true]),
+            ("♣K,JK,♣2", [false, false, false, false]),
+            (
+                "♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K",
+                [true, false, true, true],
+            ),
+            (
+                "♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K,♣A",
+                [false, true, true, true],
+            ),
+            (
+                "♣A,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K,♣A",
+                [false, false, true, false],
+            ),
+            (
+                "JK,♣2,♣3,♣4,♣5,♣6,♣7,♣8,♣9,♣10,♣J,♣Q,♣K,♣A",
+                [false, false, true, false],
+            ),
+        ];
+
+        for (cards, valid) in table {
+            for (policy, expected) in [LowOnly, HighOnly, BothEnds, NoWrap].iter().zip(&valid) {
+                let rules = RunRules {
+                    ace_policy: *policy,
+                    ..RunRules::default()
+                };
+                let run = Run::build_sequence_run_with(Cards::of(cards)?, rules);
+                assert_eq!(*expected, run.is_ok(), "{} with {:?}", cards, policy);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_ace_policy_follows_run() -> Result<(), String> {
+        let rules = RunRules {
+            ace_policy: crate::rules::AcePolicy::LowOnly,
+            ..RunRules::default()
+        };
+        let run = Run::build_sequence_run_with(Cards::of("♥J,♥Q,♥K")?, rules)?;
+        assert!(run.append(&Cards::of("♥A")?, Append::Top).is_err());
+        assert!(run.append(&Cards::of("♥10")?, Append::Bottom).is_ok());
+
+        let runs = Run::arrangements_with(&Cards::of("♥A,♥K,♥Q")?, rules);
+        assert!(runs.is_empty());
+        let runs = Run::arrangements_with(&Cards::of("♥A,♥2,♥3")?, rules);
+        assert_eq!(1, runs.len());
+        Ok(())
+    }
+
     #[test]
     fn test_burraco_score() -> Result<(), String> {
         assert_eq!(
@@ -832,4 +1510,152 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_brazilian_score() -> Result<(), String> {
+        let rules = RunRules::brazilian();
+        let clean = Run::build_sequence_run_with(Cards::of("♠3,♠4,♠5,♠6,♠7,♠8,♠9")?, rules)?;
+        assert_eq!((200, 45), clean.score_burraco_cards());
+        let long = Cards::of("♠2,♠3,♠4,♠5,♠6,♠7,♠8,♠9,♠10,♠J,♠Q,♠K,♠A")?;
+        let long = Run::build_sequence_run_with(long, rules)?;
+        assert_eq!(200, long.burraco_value());
+        let dirty = Run::build_sequence_run_with(Cards::of("♠3,♠4,♠5,♠6,♠7,♠8,♣2")?, rules)?;
+        assert_eq!((100, 45), dirty.score_burraco_cards());
+
+        assert!(Run::build_group_run_with(Cards::of("♠3,♥3,♦3")?, rules).is_err());
+        assert!(Run::arrangements_with(&Cards::of("♠3,♥3,♦3")?, rules).is_empty());
+
+        let state = BurracoState::init_with_rules(Rules::brazilian(), Some(0));
+        assert_eq!(104, state.cards_total());
+        Ok(())
+    }
+
+    #[test]
+    fn test_table_variant_deal() {
+        for table in &[
+            TableVariant::OneVsOne,
+            TableVariant::OneVsOneVsOne,
+            TableVariant::TwoVsTwo,
+        ] {
+            let rules = Rules {
+                table: *table,
+                ..Rules::default()
+            };
+            let state = BurracoState::init_with_rules(rules, Some(0));
+            assert_eq!(table.num_teams(), state.teams.len());
+            assert_eq!(table.num_players(), state.player_team_idxs.len());
+            for team in state.teams.iter() {
+                assert_eq!(table.num_team_players(), team.players.len());
+                for player in team.players.iter() {
+                    assert_eq!(table.hand_size(), player.hand.len());
+                }
+            }
+            let pot_sizes: Vec<usize> = state.pots.iter().map(|p| p.len()).collect();
+            assert_eq!(table.pot_sizes(), pot_sizes);
+            assert_eq!(108, state.cards_total());
+        }
+    }
+
+    #[test]
+    fn test_validate_deck() -> Result<(), String> {
+        let config = DeckConfig::default();
+        let mut deck = Cards::build_decks(&config);
+        assert_eq!(108, deck.len());
+        assert_eq!(4, deck.deck_counts()[Card(Jokers, Joker).deck_index()]);
+        assert_eq!(Ok(()), deck.validate_deck(&config));
+        for (i, card) in Cards::build_deck(1).iter().enumerate() {
+            assert_eq!(i, card.deck_index());
+        }
+
+        deck.retain(|c| *c != Card(Clubs, Numerical(3)));
+        deck.push(Card(Hearts, King));
+        assert_eq!(
+            Err("Not a full deck: ♣3 0 of 2, ♥K 3 of 2".into()),
+            deck.validate_deck(&config)
+        );
+
+        let mut state = BurracoState::init_seeded(2, 2, Some(0))?;
+        assert_eq!(Ok(()), state.check_cards());
+        state.open_pile.push(Card(Jokers, Joker));
+        assert!(state.check_cards().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cut() -> Result<(), String> {
+        let mut deck = Cards::of("♣3, ♣4, ♣5, ♣6")?;
+        deck.cut(1);
+        assert_eq!(Cards::of("♣6, ♣3, ♣4, ♣5")?, deck);
+        Ok(())
+    }
+
+    #[test]
+    fn test_deal_alternating() {
+        use crate::rules::DealRules;
+
+        let rules = Rules {
+            table: TableVariant::OneVsOne,
+            deal: DealRules {
+                style: DealStyle::Alternating,
+                pots_first: false,
+                cut: false,
+            },
+            ..Rules::default()
+        };
+        let deck = Cards::build_deck(1);
+        let top = |i: usize| deck[deck.len() - 1 - i];
+        let state = BurracoState::deal(rules, 0, deck.clone());
+
+        assert_eq!(1, state.player_turn);
+        // first card to the left of the dealer
+        assert!(state.teams[1].players[0].hand.contains(&top(0)));
+        assert!(state.teams[0].players[0].hand.contains(&top(1)));
+        assert!(state.teams[1].players[0].hand.contains(&top(2)));
+        // then the pots
+        assert!(state.pots[0].contains(&top(22)));
+        assert_eq!(Cards(vec![top(44)]), state.open_pile);
+    }
+
+    #[test]
+    fn test_next_deal() -> Result<(), String> {
+        let state = BurracoState::init_seeded(2, 2, Some(0))?;
+        assert_eq!((state.dealer + 1) % 4, state.player_turn);
+        let next = state.next_deal(Some(1));
+        assert_eq!((state.dealer + 1) % 4, next.dealer);
+        assert_eq!((next.dealer + 1) % 4, next.player_turn);
+        assert_eq!(state.cards_total(), next.cards_total());
+        Ok(())
+    }
+
+    #[test]
+    fn test_player_view() -> Result<(), String> {
+        let state = BurracoState::init_seeded(2, 2, Some(0))?;
+        let view = state.player_view(0);
+        assert_eq!(0, view.team.team);
+        assert_eq!(vec![(2, 11)], view.partners());
+        assert_eq!(1, view.opponents.len());
+        assert_eq!(vec![(1, 11), (3, 11)], view.opponents[0].hand_sizes);
+        assert_eq!(2, view.pots_left);
+
+        let state = BurracoState::init_seeded(2, 1, Some(0))?;
+        assert!(state.player_view(1).partners().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_next_opponent_team() -> Result<(), String> {
+        let mut state = BurracoState::init_seeded(3, 1, Some(0))?;
+        state.player_turn = 0;
+        assert_eq!(1, state.next_opponent_team());
+        state.player_turn = 2;
+        assert_eq!(0, state.next_opponent_team());
+
+        let mut state = BurracoState::init_seeded(2, 2, Some(0))?;
+        for turn in 0..4 {
+            state.player_turn = turn;
+            assert_eq!(1 - state.curr_team(), state.next_opponent_team());
+        }
+        assert!(BurracoState::init_seeded(3, 2, Some(0)).is_err());
+        Ok(())
+    }
 }
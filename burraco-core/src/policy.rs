@@ -0,0 +1,113 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::config;
+use crate::features::Features;
+use crate::features::FEATURE_NAMES;
+use crate::features::NUM_FEATURES;
+
+/// A linear policy, scoring candidate actions by a weight per feature of `FEATURE_NAMES`.
+///
+/// Kept in a file of `feature = weight` lines as the `Weights` are, the features left out
+/// weigh 0, e.g. as fitted offline to a dataset of `tournament::dump_dataset`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearPolicy {
+    pub weights: Features,
+}
+
+impl Default for LinearPolicy {
+    /// Hand picked, it plays cards towards burracos and keeps wildcards and feeding discards
+    fn default() -> Self {
+        let mut policy = LinearPolicy {
+            weights: [0.0; NUM_FEATURES],
+        };
+        let picked = [
+            ("draw_open", -2.0),
+            ("move_card", -100.0),
+            ("action_cards", 1.0),
+            ("action_wildcards", -2.0),
+            ("action_value", 0.1),
+            ("run_length_after", 0.5),
+            ("run_clean_after", 1.0),
+            ("run_burraco_after", 20.0),
+            ("discard_fits_opponent", -20.0),
+        ];
+        for (name, weight) in picked.iter() {
+            *policy.weight_mut(name).expect("a feature") = *weight;
+        }
+        policy
+    }
+}
+
+impl LinearPolicy {
+    pub fn score(&self, features: &Features) -> f64 {
+        self.weights
+            .iter()
+            .zip(features.iter())
+            .map(|(w, f)| w * f)
+            .sum()
+    }
+
+    pub fn weight_mut(&mut self, feature: &str) -> Option<&mut f64> {
+        let i = FEATURE_NAMES.iter().position(|n| *n == feature)?;
+        Some(&mut self.weights[i])
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<LinearPolicy, String> {
+        config::load(path)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        config::save(self, path)
+    }
+}
+
+impl FromStr for LinearPolicy {
+    type Err = String;
+
+    fn from_str(config: &str) -> Result<Self, Self::Err> {
+        let mut policy = LinearPolicy {
+            weights: [0.0; NUM_FEATURES],
+        };
+        config::parse_values(config, "feature", |name, weight| {
+            policy.weight_mut(name).map(|w| *w = weight).is_some()
+        })?;
+        Ok(policy)
+    }
+}
+
+impl fmt::Display for LinearPolicy {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, weight) in FEATURE_NAMES.iter().zip(self.weights.iter()) {
+            writeln!(w, "{} = {}", name, weight)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let policy: LinearPolicy = "bias = 1.5\n# comment\nhand_size = -2".parse()?;
+        let mut features = [0.0; NUM_FEATURES];
+        *features.last_mut().expect("bias") = 1.0;
+        let hand_size = FEATURE_NAMES
+            .iter()
+            .position(|n| *n == "hand_size")
+            .expect("hand_size");
+        features[hand_size] = 3.0;
+        assert_eq!(1.5 - 6.0, policy.score(&features));
+
+        let default = LinearPolicy::default();
+        assert_eq!(Ok(default.clone()), default.to_string().parse());
+        assert_eq!(
+            Err("line 1: unknown feature speed".into()),
+            "speed = 1".parse::<LinearPolicy>()
+        );
+        Ok(())
+    }
+}
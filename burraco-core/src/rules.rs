@@ -0,0 +1,289 @@
+use crate::model::Rank;
+
+/// Where an Ace may be placed in a sequence run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AcePolicy {
+    /// A-2-3, but not Q-K-A
+    LowOnly,
+    /// Q-K-A, but not A-2-3
+    HighOnly,
+    /// A-2-3 and Q-K-A, also both in the same run (A to A)
+    BothEnds,
+    /// A-2-3 and Q-K-A, but a run may not reach both ends
+    NoWrap,
+}
+
+impl AcePolicy {
+    /// Lowest and highest sequence slot a run may use, Ace low is 1 and Ace high is 14
+    pub fn slot_range(&self) -> (i16, i16) {
+        match self {
+            AcePolicy::LowOnly => (1, 13),
+            AcePolicy::HighOnly => (2, 14),
+            AcePolicy::BothEnds | AcePolicy::NoWrap => (1, 14),
+        }
+    }
+
+    pub fn max_sequence_len(&self) -> usize {
+        match self {
+            AcePolicy::BothEnds => 14,
+            _ => 13,
+        }
+    }
+}
+
+/// How cards and burracos are valued
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Scoring {
+    /// burraco 200 clean, 150 semi-clean, 100 dirty and 300 for Two to Ace
+    Italian,
+    /// canastra 200 clean and 100 dirty, Twos worth 10 and Jokers 20
+    Brazilian,
+}
+
+impl Scoring {
+    pub fn card_value(&self, rank: Rank) -> i32 {
+        match (self, rank) {
+            (Scoring::Brazilian, Rank::Two) => 10,
+            (Scoring::Brazilian, Rank::Joker) => 20,
+            _ => rank.value(),
+        }
+    }
+}
+
+/// Rules for what makes a valid run and what it is worth
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RunRules {
+    pub ace_policy: AcePolicy,
+    /// runs of the same rank, Brazilian rules only allow sequences
+    pub allow_groups: bool,
+    pub scoring: Scoring,
+}
+
+impl RunRules {
+    pub fn brazilian() -> RunRules {
+        RunRules {
+            ace_policy: AcePolicy::BothEnds,
+            allow_groups: false,
+            scoring: Scoring::Brazilian,
+        }
+    }
+}
+
+impl Default for RunRules {
+    fn default() -> Self {
+        RunRules {
+            ace_policy: AcePolicy::BothEnds,
+            allow_groups: true,
+            scoring: Scoring::Italian,
+        }
+    }
+}
+
+/// How players are seated at the table
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableVariant {
+    /// two single players
+    OneVsOne,
+    /// three single players, each with a pot to reach, the pot taken last is bigger
+    OneVsOneVsOne,
+    /// two teams, partners sitting across from each other
+    TwoVsTwo,
+}
+
+impl TableVariant {
+    pub fn from_counts(num_teams: usize, num_team_players: usize) -> Option<TableVariant> {
+        match (num_teams, num_team_players) {
+            (2, 1) => Some(TableVariant::OneVsOne),
+            (3, 1) => Some(TableVariant::OneVsOneVsOne),
+            (2, 2) => Some(TableVariant::TwoVsTwo),
+            _ => None,
+        }
+    }
+
+    pub fn num_teams(&self) -> usize {
+        match self {
+            TableVariant::OneVsOne | TableVariant::TwoVsTwo => 2,
+            TableVariant::OneVsOneVsOne => 3,
+        }
+    }
+
+    pub fn num_team_players(&self) -> usize {
+        match self {
+            TableVariant::OneVsOne | TableVariant::OneVsOneVsOne => 1,
+            TableVariant::TwoVsTwo => 2,
+        }
+    }
+
+    pub fn num_players(&self) -> usize {
+        self.num_teams() * self.num_team_players()
+    }
+
+    /// Number of cards in each hand when dealing, the same at every table
+    pub fn hand_size(&self) -> usize {
+        11
+    }
+
+    /// Number of cards in each pot, one pot per team, in the order the pots are taken
+    pub fn pot_sizes(&self) -> Vec<usize> {
+        match self {
+            TableVariant::OneVsOne | TableVariant::TwoVsTwo => vec![11, 11],
+            // the last player still without a pot has the most to make up
+            TableVariant::OneVsOneVsOne => vec![11, 11, 18],
+        }
+    }
+}
+
+/// What drawing from the open pile gives the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum OpenPileDraw {
+    /// the whole open pile
+    WholePile,
+    /// only the top card of the open pile
+    TopCardOnly,
+    /// the whole open pile, and the top card must be used in the next play
+    WholePileMustUseTop,
+}
+
+/// What happens when discarding a card the next opponent could attach to their runs as the last
+/// card of the hand, the "scarto"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FeedingDiscard {
+    Allowed,
+    Forbidden,
+    /// points deducted from the team of the discarding player
+    Penalty(i32),
+}
+
+/// Restrictions on the discard, the soft ones are waived when no other card could be discarded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DiscardRules {
+    /// whether the game may be closed by discarding a Joker or a Two
+    pub wildcard_close: bool,
+    /// whether a single card taken from the open pile may be discarded right away (soft)
+    pub discard_picked_single: bool,
+    /// for the last card only, the plays leaving a forbidden one are not allowed
+    pub feeding: FeedingDiscard,
+}
+
+impl DiscardRules {
+    /// As played in tournaments
+    pub fn federation() -> DiscardRules {
+        DiscardRules {
+            wildcard_close: false,
+            discard_picked_single: false,
+            feeding: FeedingDiscard::Forbidden,
+        }
+    }
+}
+
+impl Default for DiscardRules {
+    fn default() -> Self {
+        DiscardRules {
+            wildcard_close: true,
+            discard_picked_single: true,
+            feeding: FeedingDiscard::Allowed,
+        }
+    }
+}
+
+/// How the hands are dealt
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DealStyle {
+    /// a whole hand at a time, team by team
+    Packets,
+    /// one card at a time to each player, starting left of the dealer, as at a real table
+    Alternating,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DealRules {
+    pub style: DealStyle,
+    /// whether the pots are taken off the deck before the hands are dealt
+    pub pots_first: bool,
+    /// whether the deck is cut at a random position before dealing
+    pub cut: bool,
+}
+
+impl Default for DealRules {
+    fn default() -> Self {
+        DealRules {
+            style: DealStyle::Packets,
+            pots_first: true,
+            cut: false,
+        }
+    }
+}
+
+/// The cards a game is played with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeckConfig {
+    /// decks of 52 cards
+    pub num_decks: usize,
+    pub jokers_per_deck: usize,
+}
+
+impl DeckConfig {
+    /// Number of cards in all the decks
+    pub fn size(&self) -> usize {
+        self.num_decks * (52 + self.jokers_per_deck)
+    }
+}
+
+impl Default for DeckConfig {
+    fn default() -> Self {
+        DeckConfig {
+            num_decks: 2,
+            jokers_per_deck: 2,
+        }
+    }
+}
+
+/// The rules a game is played with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub table: TableVariant,
+    pub deal: DealRules,
+    pub open_pile_draw: OpenPileDraw,
+    pub run: RunRules,
+    pub discard: DiscardRules,
+    pub deck: DeckConfig,
+    /// for the team of the player emptying their hand to end the game
+    pub going_out_bonus: i32,
+    /// whether a player may ask their partner "may I close?", see `BurracoGame::ask_close`
+    pub close_query: bool,
+    /// whether a team needs a burraco (canastra) among its runs to end the game
+    pub close_needs_burraco: bool,
+}
+
+impl Rules {
+    /// Buraco as played in Brazil: only sequences, no jokers and a bonus for going out, which
+    /// needs a canastra
+    pub fn brazilian() -> Rules {
+        Rules {
+            run: RunRules::brazilian(),
+            deck: DeckConfig {
+                num_decks: 2,
+                jokers_per_deck: 0,
+            },
+            going_out_bonus: 100,
+            close_needs_burraco: true,
+            ..Rules::default()
+        }
+    }
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            table: TableVariant::TwoVsTwo,
+            deal: DealRules::default(),
+            open_pile_draw: OpenPileDraw::WholePile,
+            run: RunRules::default(),
+            discard: DiscardRules::default(),
+            deck: DeckConfig::default(),
+            going_out_bonus: 0,
+            close_query: false,
+            close_needs_burraco: false,
+        }
+    }
+}
@@ -0,0 +1,362 @@
+use rand::prelude::*;
+
+use crate::actions::BurracoGame;
+use crate::actions::GamePhase;
+use crate::model::BurracoState;
+use crate::model::Cards;
+use crate::model::Run;
+use crate::rules::Rules;
+
+/// Builds a game position from card notation, e.g. `"♠4, ♠5, JK"`, for tests and puzzles.
+///
+/// The cards are taken from the decks of the rules, so no card can be used more often than the
+/// decks have it. The cards not placed are dealt to the hands and pots that are not set, then
+/// go under the draw pile.
+#[derive(Debug, Clone)]
+pub struct ScenarioBuilder {
+    rules: Rules,
+    seed: u64,
+    /// (seat, cards), seat as in `player_turn`
+    hands: Vec<(usize, String)>,
+    /// (team, cards)
+    runs: Vec<(usize, String)>,
+    /// (pot, cards)
+    pots: Vec<(usize, String)>,
+    open_pile: Option<String>,
+    draw_pile: String,
+    reached_pot: Vec<usize>,
+    leave_out_rest: bool,
+    player_turn: usize,
+    phase: GamePhase,
+}
+
+impl ScenarioBuilder {
+    pub fn new(rules: Rules) -> ScenarioBuilder {
+        ScenarioBuilder {
+            rules,
+            seed: 0,
+            hands: Vec::new(),
+            runs: Vec::new(),
+            pots: Vec::new(),
+            open_pile: None,
+            draw_pile: String::new(),
+            reached_pot: Vec::new(),
+            leave_out_rest: false,
+            player_turn: 0,
+            phase: GamePhase::Draw,
+        }
+    }
+
+    /// For shuffling the cards that are not placed
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn hand(mut self, seat: usize, cards: &str) -> Self {
+        self.hands.push((seat, cards.into()));
+        self
+    }
+
+    /// A run of the team, a sequence or a group depending on the cards
+    pub fn run(mut self, team: usize, cards: &str) -> Self {
+        self.runs.push((team, cards.into()));
+        self
+    }
+
+    pub fn pot(mut self, pot: usize, cards: &str) -> Self {
+        self.pots.push((pot, cards.into()));
+        self
+    }
+
+    /// Bottom to top, a single card from the deck when not set
+    pub fn open_pile(mut self, cards: &str) -> Self {
+        self.open_pile = Some(cards.into());
+        self
+    }
+
+    /// Top first, the cards that are not placed go below
+    pub fn draw_pile(mut self, cards: &str) -> Self {
+        self.draw_pile = cards.into();
+        self
+    }
+
+    /// The team has taken a pot, the pots are taken in order as by `BurracoState::take_pot`
+    pub fn reached_pot(mut self, team: usize) -> Self {
+        self.reached_pot.push(team);
+        self
+    }
+
+    /// Leaves the cards that are not placed out of the draw pile, e.g. for the end of a game
+    pub fn leave_out_rest(mut self) -> Self {
+        self.leave_out_rest = true;
+        self
+    }
+
+    /// The seat whose turn it is, as in `player_turn`
+    pub fn turn(mut self, seat: usize) -> Self {
+        self.player_turn = seat;
+        self
+    }
+
+    pub fn phase(mut self, phase: GamePhase) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    pub fn build_state(&self) -> Result<BurracoState, String> {
+        let rules = &self.rules;
+        let num_teams = rules.table.num_teams();
+        let num_players = rules.table.num_players();
+        let pot_sizes = rules.table.pot_sizes();
+        if self.player_turn >= num_players {
+            return Err(format!("No seat {} at the table", self.player_turn));
+        }
+
+        let mut deck = Cards::build_decks(&rules.deck);
+        deck.shuffle(&mut StdRng::seed_from_u64(self.seed));
+        let mut take = |expr: &str| -> Result<Cards, String> {
+            let cards = Cards::of(expr)?;
+            for card in cards.iter() {
+                let index = deck
+                    .iter()
+                    .position(|c| c == card)
+                    .ok_or_else(|| format!("No {} left in the deck", card))?;
+                deck.remove(index);
+            }
+            Ok(cards)
+        };
+
+        let mut hands = vec![None; num_players];
+        for (seat, expr) in self.hands.iter() {
+            if *seat >= num_players {
+                return Err(format!("No seat {} at the table", seat));
+            }
+            hands[*seat] = Some(take(expr)?);
+        }
+        let mut runs = vec![Vec::new(); num_teams];
+        for (team, expr) in self.runs.iter() {
+            if *team >= num_teams {
+                return Err(format!("No team {} at the table", team));
+            }
+            runs[*team].push(Run::try_build_with(take(expr)?, rules.run)?);
+        }
+        let mut pots = vec![None; pot_sizes.len()];
+        for (pot, expr) in self.pots.iter() {
+            if *pot >= pot_sizes.len() {
+                return Err(format!("No pot {} at the table", pot));
+            }
+            pots[*pot] = Some(take(expr)?);
+        }
+        let open_pile = match &self.open_pile {
+            Some(expr) => Some(take(expr)?),
+            None => None,
+        };
+        let draw_pile_top = take(&self.draw_pile)?;
+
+        let mut reached_pot = self.reached_pot.clone();
+        reached_pot.sort_unstable();
+        reached_pot.dedup();
+        if let Some(team) = reached_pot.iter().find(|t| **t >= num_teams) {
+            return Err(format!("No team {} at the table", team));
+        }
+        if reached_pot.len() > pot_sizes.len() {
+            return Err(format!("Only {} pots to take", pot_sizes.len()));
+        }
+        if let Some(pot) = pots[..reached_pot.len()].iter().position(|p| p.is_some()) {
+            return Err(format!("Pot {} is set, but taken", pot));
+        }
+
+        let taken_pots = pots
+            .into_iter()
+            .enumerate()
+            .map(|(i, pot)| match pot {
+                _ if i < reached_pot.len() => Some(Cards::default()),
+                pot => pot,
+            })
+            .collect();
+        let hand_sizes = vec![rules.table.hand_size(); num_players];
+        let mut state = seat_cards(
+            rules,
+            self.player_turn,
+            hands,
+            &hand_sizes,
+            taken_pots,
+            open_pile,
+            deck,
+        )?;
+        if self.leave_out_rest {
+            state.draw_pile = Cards::default();
+        }
+        state.draw_pile.extend(draw_pile_top.iter().rev());
+        for (team, team_runs) in runs.into_iter().enumerate() {
+            state.teams[team].played_runs = team_runs;
+        }
+        for team in reached_pot {
+            state.teams[team].has_reached_pot = true;
+        }
+        Ok(state)
+    }
+
+    /// The game at the position, which has to keep the invariants of a game
+    pub fn build(&self) -> Result<BurracoGame, String> {
+        let game = BurracoGame::at_phase(self.build_state()?, self.phase);
+        game.check_invariants()?;
+        Ok(game)
+    }
+}
+
+/// The cards at a table where `player_turn` is to play. The hands, pots and open pile not
+/// given are dealt from the back of `deck`, the hands of `hand_sizes` by seat, and what is
+/// left of the deck becomes the draw pile. The runs and the pots reached are left as dealt.
+pub(crate) fn seat_cards(
+    rules: &Rules,
+    player_turn: usize,
+    hands: Vec<Option<Cards>>,
+    hand_sizes: &[usize],
+    pots: Vec<Option<Cards>>,
+    open_pile: Option<Cards>,
+    mut deck: Cards,
+) -> Result<BurracoState, String> {
+    let num_players = rules.table.num_players();
+    let pot_sizes = rules.table.pot_sizes();
+    let mut deal = |num_cards: usize| -> Result<Cards, String> {
+        if deck.len() < num_cards {
+            return Err("Not enough cards left in the deck".into());
+        }
+        Ok(deck.drain_back(num_cards))
+    };
+
+    let dealer = (player_turn + num_players - 1) % num_players;
+    // dealt from an unshuffled deck for the seats, every card is replaced below
+    let mut state = BurracoState::deal(rules.clone(), dealer, Cards::build_decks(&rules.deck));
+    for (seat, hand) in hands.into_iter().enumerate() {
+        let mut hand = match hand {
+            Some(hand) => hand,
+            None => deal(hand_sizes[seat])?,
+        };
+        hand.sort();
+        let (team, player) = state.player_team_idxs[seat];
+        state.teams[team].players[player].hand = hand;
+    }
+    for (i, pot) in pots.into_iter().enumerate() {
+        state.pots[i] = match pot {
+            Some(pot) => pot,
+            None => deal(pot_sizes[i])?,
+        };
+    }
+    state.open_pile = match open_pile {
+        Some(open_pile) => open_pile,
+        None => deal(1)?,
+    };
+    state.draw_pile = deck;
+    state.player_turn = player_turn;
+    Ok(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actions::DrawAction;
+    use crate::model::Card;
+    use crate::model::Rank::*;
+    use crate::model::Suit::*;
+    use crate::rules::TableVariant;
+
+    #[test]
+    fn test_build() -> Result<(), String> {
+        let mut game = ScenarioBuilder::new(Rules::default())
+            .hand(1, "♠4, ♠5, ♥9")
+            .run(0, "♣3, ♣4, ♣5")
+            .run(1, "♥Q, ♠Q, JK")
+            .open_pile("♦7, ♦K")
+            .draw_pile("♥A, ♣2")
+            .turn(1)
+            .build()?;
+
+        let state = game.state();
+        assert_eq!(Ok(()), state.check_cards());
+        assert_eq!(1, state.player_turn);
+        assert_eq!(0, state.dealer);
+        assert_eq!(Cards::of("♥9, ♠4, ♠5")?, game.current_player().hand);
+        assert_eq!(11, state.teams[0].players[0].hand.len());
+        assert_eq!(
+            Cards::of("♣3, ♣4, ♣5")?,
+            *state.teams[0].played_runs[0].cards()
+        );
+        assert_eq!(1, state.teams[1].played_runs.len());
+        assert_eq!(Cards::of("♦7, ♦K")?, state.open_pile);
+        assert!(state.pots.iter().all(|p| p.len() == 11));
+
+        game.draw(DrawAction::DrawPile)?;
+        assert!(game.current_player().hand.contains(&Card(Hearts, Ace)));
+        assert_eq!(Some(&Card(Clubs, Two)), game.state().draw_pile.last());
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_pots() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .reached_pot(1)
+            .pot(1, "♠3, ♠4, ♠5, ♠6, ♠7, ♠8, ♠9, ♠10, ♠J, ♠Q, ♠K")
+            .phase(GamePhase::Play)
+            .build()?;
+        assert!(game.state().pots[0].is_empty());
+        assert_eq!(Cards::of("♠3")?, Cards(game.state().pots[1][..1].to_vec()));
+        assert!(game.state().teams[1].has_reached_pot);
+        assert_eq!(GamePhase::Play, game.phase());
+
+        let taken = ScenarioBuilder::new(Rules::default())
+            .reached_pot(0)
+            .pot(0, "♠3")
+            .build();
+        assert_eq!(Err("Pot 0 is set, but taken".into()), taken.map(|_| ()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let rules = Rules::default();
+        let too_many = ScenarioBuilder::new(rules.clone())
+            .hand(0, "♠4, ♠4")
+            .run(1, "♠3, ♠4, ♠5")
+            .build_state();
+        assert_eq!(Err("No ♠4 left in the deck".into()), too_many.map(|_| ()));
+
+        let bad_run = ScenarioBuilder::new(rules.clone())
+            .run(0, "♠3, ♥4, ♠5")
+            .build_state();
+        assert!(bad_run.is_err());
+
+        let empty_hand = ScenarioBuilder::new(rules.clone()).hand(2, "").build();
+        assert!(empty_hand.is_err());
+
+        let three_players = Rules {
+            table: TableVariant::OneVsOneVsOne,
+            ..rules
+        };
+        assert!(ScenarioBuilder::new(three_players.clone())
+            .turn(3)
+            .build()
+            .is_err());
+        assert!(ScenarioBuilder::new(three_players).turn(2).build().is_ok());
+    }
+
+    #[test]
+    fn test_leave_out_rest() -> Result<(), String> {
+        let game = ScenarioBuilder::new(Rules::default())
+            .hand(0, "♠4")
+            .hand(1, "♠5")
+            .hand(2, "♠6")
+            .hand(3, "♠7")
+            .reached_pot(0)
+            .reached_pot(1)
+            .open_pile("♥K")
+            .draw_pile("♣3, ♣4")
+            .leave_out_rest()
+            .build()?;
+        assert_eq!(Cards::of("♣4, ♣3")?, game.state().draw_pile);
+        assert_eq!(7, game.state().cards_total());
+        Ok(())
+    }
+}
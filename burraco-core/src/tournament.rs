@@ -0,0 +1,314 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::ops::Range;
+use std::rc::Rc;
+
+use crate::actions::Action;
+use crate::actions::BurracoGame;
+use crate::actions::DiscardAction;
+use crate::actions::DrawAction;
+use crate::actions::GamePhase;
+use crate::actions::PlayAction;
+use crate::agent::create_agent;
+use crate::agent::AgentType;
+use crate::agent::BurracoAgent;
+use crate::features::FeatureExtractor;
+use crate::features::Features;
+use crate::features::FEATURE_NAMES;
+use crate::model::BurracoState;
+use crate::model::Cards;
+use crate::model::PlayerView;
+
+/// The agents by seat, the seats going around the teams as dealt by `BurracoState::init_seeded`
+fn seat_agents(teams: &[&[AgentType]]) -> Vec<Box<dyn BurracoAgent>> {
+    let num_teams = teams.len();
+    (0..num_teams * teams[0].len())
+        .map(|seat| create_agent(teams[seat % num_teams][seat / num_teams].clone()))
+        .collect()
+}
+
+/// Lets the current player ask their partner "may I close?" when the rules allow it and their
+/// agent wants to, see `BurracoGame::ask_close`. Returns the answer, None when not asked.
+pub fn offer_close_query(
+    game: &mut BurracoGame,
+    agents: &mut [Box<dyn BurracoAgent>],
+) -> Result<Option<bool>, String> {
+    if game.close_query_partner().is_err() {
+        return Ok(None);
+    }
+    let seat = game.state().player_turn;
+    if !agents[seat].wants_to_ask_close(&game.state().player_view(seat)) {
+        return Ok(None);
+    }
+    let may_close = game.ask_close(|partner, view| agents[partner].answer_close_query(view))?;
+    Ok(Some(may_close))
+}
+
+/// Plays the game to the end with the agents by seat, as in `player_turn`, and returns the
+/// winning team
+pub fn play_game(
+    game: &mut BurracoGame,
+    agents: &mut [Box<dyn BurracoAgent>],
+) -> Result<usize, String> {
+    loop {
+        if game.phase() == GamePhase::Play {
+            offer_close_query(game, agents)?;
+        }
+        let agent = &mut agents[game.state().player_turn];
+        match game.phase() {
+            GamePhase::Draw => game.draw(agent.select_draw_action(game.state()))?,
+            GamePhase::Play => {
+                // as many moves as there are runs at the start of the turn
+                let mut moves_allowed = game.current_team().played_runs.len();
+                while game.phase() == GamePhase::Play {
+                    let actions = game.play_actions(moves_allowed);
+                    let action = agent.select_play_action(actions, game.state());
+                    if let PlayAction::MoveCard(_, _, _) = action {
+                        moves_allowed -= 1;
+                    }
+                    game.play(action)?;
+                }
+            }
+            GamePhase::Discard => {
+                let choices = game.discard_choices();
+                game.discard(agent.select_discard_action(&choices, game.state()))?
+            }
+            GamePhase::Finished(winning_team) => return Ok(winning_team),
+        }
+    }
+}
+
+/// Wins of each team over the seeded games, the agents of `teams[i]` playing for team i
+pub fn win_counts(teams: &[&[AgentType]], seeds: Range<u64>) -> Result<Vec<usize>, String> {
+    let num_teams = teams.len();
+    let num_team_players = teams[0].len();
+    let mut wins = vec![0; num_teams];
+    for seed in seeds {
+        let mut agents = seat_agents(teams);
+        let state = BurracoState::init_seeded(num_teams, num_team_players, Some(seed))?;
+        let mut game = BurracoGame::from(state);
+        wins[play_game(&mut game, &mut agents)?] += 1;
+    }
+    Ok(wins)
+}
+
+/// A choice of an agent, with the features of every candidate action
+struct Decision {
+    team: usize,
+    candidates: Vec<(Features, Action)>,
+    chosen: usize,
+}
+
+/// Keeps the decisions of the agent it wraps
+struct RecordingAgent {
+    agent: Box<dyn BurracoAgent>,
+    decisions: Rc<RefCell<Vec<Decision>>>,
+}
+
+impl RecordingAgent {
+    fn record(&self, state: &BurracoState, candidates: Vec<Action>, chosen: usize) {
+        let view = state.player_view(state.player_turn);
+        let extractor = FeatureExtractor::new(&view);
+        let candidates = candidates
+            .into_iter()
+            .map(|action| (extractor.extract(&action), action))
+            .collect();
+        self.decisions.borrow_mut().push(Decision {
+            team: state.curr_team(),
+            candidates,
+            chosen,
+        });
+    }
+}
+
+impl BurracoAgent for RecordingAgent {
+    fn select_draw_action(&mut self, state: &BurracoState) -> DrawAction {
+        let action = self.agent.select_draw_action(state);
+        let allowed = DrawAction::allowed(state);
+        let chosen = allowed.iter().position(|a| *a == action).unwrap_or(0);
+        self.record(
+            state,
+            allowed.into_iter().map(Action::Draw).collect(),
+            chosen,
+        );
+        action
+    }
+
+    fn select_play_action(
+        &mut self,
+        actions: Vec<(PlayAction, i32)>,
+        state: &BurracoState,
+    ) -> PlayAction {
+        let candidates: Vec<Action> = actions
+            .iter()
+            .map(|(a, _)| Action::Play(a.clone()))
+            .collect();
+        let action = self.agent.select_play_action(actions, state);
+        let chosen = candidates
+            .iter()
+            .position(|a| matches!(a, Action::Play(play) if *play == action))
+            .unwrap_or(0);
+        self.record(state, candidates, chosen);
+        action
+    }
+
+    fn select_discard_action(&mut self, hand: &Cards, state: &BurracoState) -> DiscardAction {
+        let action = self.agent.select_discard_action(hand, state);
+        let chosen = hand.iter().position(|c| *c == action.0).unwrap_or(0);
+        let candidates = hand
+            .iter()
+            .map(|c| Action::Discard(DiscardAction(*c)))
+            .collect();
+        self.record(state, candidates, chosen);
+        action
+    }
+
+    fn wants_to_ask_close(&mut self, view: &PlayerView) -> bool {
+        self.agent.wants_to_ask_close(view)
+    }
+
+    fn answer_close_query(&mut self, view: &PlayerView) -> bool {
+        self.agent.answer_close_query(view)
+    }
+
+    fn display(&self) -> String {
+        self.agent.display()
+    }
+}
+
+/// Plays the seeded games and writes a CSV row for every candidate action of every decision,
+/// for training a policy offline: the game seed, the decision, the team, whether the action was
+/// chosen, the features, the action and the outcome, the final score of the team less the best
+/// score of the others. Returns the number of rows.
+pub fn dump_dataset(
+    teams: &[&[AgentType]],
+    seeds: Range<u64>,
+    out: &mut impl Write,
+) -> Result<usize, String> {
+    let num_teams = teams.len();
+    let num_team_players = teams[0].len();
+    let io_error = |e: std::io::Error| e.to_string();
+    writeln!(
+        out,
+        "game,decision,team,chosen,{},action,outcome",
+        FEATURE_NAMES.join(",")
+    )
+    .map_err(io_error)?;
+
+    let mut rows = 0;
+    for seed in seeds {
+        let decisions = Rc::new(RefCell::new(Vec::new()));
+        let mut agents: Vec<Box<dyn BurracoAgent>> = seat_agents(teams)
+            .into_iter()
+            .map(|agent| {
+                Box::new(RecordingAgent {
+                    agent,
+                    decisions: decisions.clone(),
+                }) as Box<dyn BurracoAgent>
+            })
+            .collect();
+        let state = BurracoState::init_seeded(num_teams, num_team_players, Some(seed))?;
+        let mut game = BurracoGame::from(state);
+        play_game(&mut game, &mut agents)?;
+
+        let scoreboard = game.scoreboard();
+        let outcome = |team: usize| {
+            let best_other = (0..num_teams)
+                .filter(|t| *t != team)
+                .map(|t| scoreboard[t])
+                .max()
+                .unwrap_or(0);
+            scoreboard[team] - best_other
+        };
+        for (i, decision) in decisions.borrow().iter().enumerate() {
+            for (j, (features, action)) in decision.candidates.iter().enumerate() {
+                let features: Vec<String> = features.iter().map(|f| f.to_string()).collect();
+                writeln!(
+                    out,
+                    "{},{},{},{},{},\"{}\",{}",
+                    seed,
+                    i,
+                    decision.team,
+                    (j == decision.chosen) as u8,
+                    features.join(","),
+                    action.to_string().replace('"', "\"\""),
+                    outcome(decision.team)
+                )
+                .map_err(io_error)?;
+                rows += 1;
+            }
+        }
+    }
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_win_counts() -> Result<(), String> {
+        let teams = [
+            &[AgentType::Dumb, AgentType::Dumb][..],
+            &[AgentType::Smart, AgentType::Smart][..],
+        ];
+        let wins = win_counts(&teams, 0..4)?;
+        assert_eq!(4, wins.iter().sum::<usize>());
+        assert_eq!(wins, win_counts(&teams, 0..4)?);
+
+        let three_teams_of_two = [teams[1]; 3];
+        assert_eq!(
+            Err("Unsupported table: 3 teams of 2 players".into()),
+            win_counts(&three_teams_of_two, 0..1)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_offer_close_query() -> Result<(), String> {
+        use crate::rules::Rules;
+        use crate::scenario::ScenarioBuilder;
+
+        let teams = [
+            &[AgentType::Smart, AgentType::Smart][..],
+            &[AgentType::Smart, AgentType::Smart][..],
+        ];
+        let mut agents = seat_agents(&teams);
+        let scenario = ScenarioBuilder::new(Rules {
+            close_query: true,
+            ..Rules::default()
+        })
+        .phase(GamePhase::Play);
+
+        // a full hand is far from closing
+        let mut game = scenario.clone().build()?;
+        assert_eq!(Ok(None), offer_close_query(&mut game, &mut agents));
+
+        let mut game = scenario.hand(0, "♠4, ♠5, ♠6").reached_pot(0).build()?;
+        assert_eq!(Ok(Some(true)), offer_close_query(&mut game, &mut agents));
+        assert_eq!(Some(true), game.state().close_answer);
+        // asked once a turn
+        assert_eq!(Ok(None), offer_close_query(&mut game, &mut agents));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dump_dataset() -> Result<(), String> {
+        let teams = [&[AgentType::Smart][..], &[AgentType::Dumb][..]];
+        let mut out = Vec::new();
+        let rows = dump_dataset(&teams, 0..2, &mut out)?;
+        let csv = String::from_utf8(out).map_err(|e| e.to_string())?;
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows + 1, lines.len());
+        assert!(lines[0].starts_with("game,decision,team,chosen,hand_clubs,"));
+        assert!(lines[0].ends_with(",bias,action,outcome"));
+        assert!(lines[1].starts_with("0,0,"));
+        // draw from the hidden pile, or take the open pile
+        assert!(lines[1].contains(",\"Collect open pile\",") || lines[1].contains(",\"Draw"));
+
+        let first_decision = lines[1..].iter().take_while(|l| l.starts_with("0,0,"));
+        let chosen = first_decision.filter(|l| l.split(',').nth(3) == Some("1"));
+        assert_eq!(1, chosen.count());
+        Ok(())
+    }
+}
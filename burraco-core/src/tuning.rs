@@ -0,0 +1,109 @@
+use rand::prelude::*;
+
+use crate::agent::AgentType;
+use crate::tournament::win_counts;
+use crate::weights::Weights;
+
+/// Settings of `tune`, the same settings and seed tune to the same weights
+#[derive(Debug, Clone, PartialEq)]
+pub struct TuningConfig {
+    pub seed: u64,
+    pub rounds: usize,
+    /// per side, every candidate plays the games as either team
+    pub games: u64,
+    pub num_team_players: usize,
+    /// relative size of the first changes, adapted by how often they win
+    pub step: f64,
+    /// a candidate has to win more than this, so luck of the deals is not taken for progress
+    pub min_win_rate: f64,
+}
+
+impl Default for TuningConfig {
+    fn default() -> Self {
+        TuningConfig {
+            seed: 0,
+            rounds: 50,
+            games: 100,
+            num_team_players: 1,
+            step: 0.3,
+            min_win_rate: 0.53,
+        }
+    }
+}
+
+/// Win rate of the candidate against the incumbent on the same deals, playing either team
+fn win_rate(
+    candidate: Weights,
+    incumbent: Weights,
+    config: &TuningConfig,
+    first_seed: u64,
+) -> Result<f64, String> {
+    let candidate = vec![AgentType::Weighted(candidate); config.num_team_players];
+    let incumbent = vec![AgentType::Weighted(incumbent); config.num_team_players];
+    let seeds = first_seed..first_seed + config.games;
+    let first = win_counts(&[&candidate, &incumbent], seeds.clone())?;
+    let second = win_counts(&[&incumbent, &candidate], seeds)?;
+    Ok((first[0] + second[1]) as f64 / (2 * config.games) as f64)
+}
+
+/// Hill climbs the weights through self-play: each round every weight is changed at random,
+/// and the candidate replaces the best weights when it wins enough of its games against them.
+/// The step grows after a win and shrinks after a loss, a (1+1) evolution strategy.
+///
+/// `progress` sees every round with the best weights and the win rate of the candidate.
+pub fn tune(
+    start: Weights,
+    config: &TuningConfig,
+    mut progress: impl FnMut(usize, &Weights, f64),
+) -> Result<Weights, String> {
+    let mut rng = StdRng::seed_from_u64(config.seed);
+    let mut best = start;
+    let mut step = config.step;
+    for round in 0..config.rounds {
+        let mut candidate = best;
+        for (name, value) in best.values().iter() {
+            let change = rng.gen_range(-step..=step) * value.abs().max(1.0);
+            *candidate.value_mut(name).expect("named by values") = value + change;
+        }
+        // new deals every round, so the weights do not fit a few games
+        // room for the seeds of the games after the first
+        let first_seed = rng.gen_range(0..u64::MAX - config.games);
+        let rate = win_rate(candidate, best, config, first_seed)?;
+        if rate > config.min_win_rate {
+            best = candidate;
+            step *= 1.5;
+        } else {
+            // not so small that the candidates play as the best weights do
+            step = (step * 0.9).max(config.step / 10.0);
+        }
+        progress(round, &best, rate);
+    }
+    Ok(best)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tune_is_reproducible() -> Result<(), String> {
+        let config = TuningConfig {
+            seed: 3,
+            rounds: 3,
+            games: 2,
+            ..TuningConfig::default()
+        };
+        let run = || -> Result<(Weights, Vec<f64>), String> {
+            let mut rates = vec![];
+            let tuned = tune(Weights::default(), &config, |_round, _best, rate| {
+                rates.push(rate)
+            })?;
+            Ok((tuned, rates))
+        };
+        let (tuned, rates) = run()?;
+        assert_eq!(3, rates.len());
+        assert!(rates.iter().all(|r| (0.0..=1.0).contains(r)));
+        assert_eq!((tuned, rates), run()?);
+        Ok(())
+    }
+}
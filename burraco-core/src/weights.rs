@@ -0,0 +1,140 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::config;
+
+/// Weights of the `WeightedAgent`, each multiplying a feature of a play or a discard.
+///
+/// Kept in a config file of `name = value` lines, where `#` starts a comment and a weight
+/// that is left out keeps its default:
+///
+/// ```text
+/// # holds on to wildcards longer
+/// wildcard_use = -12
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Weights {
+    /// per point the play scores
+    pub score: f64,
+    /// per wildcard the play puts on the table, a replaced wildcard counts as minus one
+    pub wildcard_use: f64,
+    /// per card of the run after the play
+    pub run_length: f64,
+    /// per card of a clean run after the play, up to the 7 of a clean burraco
+    pub clean_burraco: f64,
+    /// per card left in hand after the play
+    pub cards_left: f64,
+    /// for emptying the hand, divided by one more than the cards left after the play
+    pub pot_proximity: f64,
+    /// per point the next opponent is expected to gain from the discard, see `estimate_discard`
+    pub discard_danger: f64,
+}
+
+impl Default for Weights {
+    /// Hand picked, they win about two thirds of the games against the `SmartAgent`
+    fn default() -> Self {
+        Weights {
+            score: 0.1,
+            wildcard_use: -4.0,
+            run_length: 1.0,
+            clean_burraco: 1.0,
+            cards_left: -3.0,
+            pot_proximity: 10.0,
+            discard_danger: 1.0,
+        }
+    }
+}
+
+impl Weights {
+    /// The weights by name, in the order they are written
+    pub fn values(&self) -> [(&'static str, f64); 7] {
+        [
+            ("score", self.score),
+            ("wildcard_use", self.wildcard_use),
+            ("run_length", self.run_length),
+            ("clean_burraco", self.clean_burraco),
+            ("cards_left", self.cards_left),
+            ("pot_proximity", self.pot_proximity),
+            ("discard_danger", self.discard_danger),
+        ]
+    }
+
+    pub fn value_mut(&mut self, name: &str) -> Option<&mut f64> {
+        match name {
+            "score" => Some(&mut self.score),
+            "wildcard_use" => Some(&mut self.wildcard_use),
+            "run_length" => Some(&mut self.run_length),
+            "clean_burraco" => Some(&mut self.clean_burraco),
+            "cards_left" => Some(&mut self.cards_left),
+            "pot_proximity" => Some(&mut self.pot_proximity),
+            "discard_danger" => Some(&mut self.discard_danger),
+            _ => None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Weights, String> {
+        config::load(path)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), String> {
+        config::save(self, path)
+    }
+}
+
+impl FromStr for Weights {
+    type Err = String;
+
+    fn from_str(config: &str) -> Result<Self, Self::Err> {
+        let mut weights = Weights::default();
+        config::parse_values(config, "weight", |name, value| {
+            weights
+                .value_mut(name)
+                .map(|weight| *weight = value)
+                .is_some()
+        })?;
+        Ok(weights)
+    }
+}
+
+impl fmt::Display for Weights {
+    fn fmt(&self, w: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, value) in self.values().iter() {
+            writeln!(w, "{} = {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let weights: Weights =
+            "# comment\n\n wildcard_use = -12 \nscore=0.5 # per point\n".parse()?;
+        assert_eq!(-12.0, weights.wildcard_use);
+        assert_eq!(0.5, weights.score);
+        assert_eq!(Weights::default().run_length, weights.run_length);
+
+        assert_eq!(Ok(weights), weights.to_string().parse());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err("line 2: unknown weight speed".into()),
+            "score = 1\nspeed = 2".parse::<Weights>()
+        );
+        assert_eq!(
+            Err("line 1: invalid value for score".into()),
+            "score = high".parse::<Weights>()
+        );
+        assert_eq!(
+            Err("line 1: expected `name = value`".into()),
+            "score".parse::<Weights>()
+        );
+    }
+}
@@ -4,11 +4,22 @@ use burraco::actions::GamePhase::*;
 use burraco::actions::PlayAction;
 use burraco::agent::create_agent;
 use burraco::agent::AgentType;
+use burraco::difficulty::Difficulty;
 use burraco::model::BurracoState;
+use burraco::policy::LinearPolicy;
+use burraco::weights::Weights;
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
 
 type PlayedAction = (u32, usize, Action);
 
-fn run_match(teams: &[&[AgentType]], seed: u64) -> Result<(usize, Vec<PlayedAction>), String> {
+/// winning team, scoreboard and the actions played
+type MatchResult = (usize, Vec<i32>, Vec<PlayedAction>);
+
+fn run_match(teams: &[&[AgentType]], seed: u64) -> Result<MatchResult, String> {
     let num_teams = teams.len();
     let num_team_players = teams[0].len();
     let num_players = num_teams * num_team_players;
@@ -18,27 +29,18 @@ fn run_match(teams: &[&[AgentType]], seed: u64) -> Result<(usize, Vec<PlayedActi
         let team = i % num_teams;
         let team_player = i / num_teams;
         let agent_type = &teams[team][team_player];
-        let agent = create_agent(*agent_type);
+        let agent = create_agent(agent_type.clone());
         agents.push(agent);
     }
 
-    let state = BurracoState::init_seeded(num_teams, num_team_players, Some(seed));
+    let state = BurracoState::init_seeded(num_teams, num_team_players, Some(seed))?;
     let mut game = BurracoGame::from(state);
 
-    let orig_cards = game.state().cards_total();
-
     let mut played_actions: Vec<PlayedAction> = Vec::new();
 
     'round: loop {
         let player = game.state().player_turn;
         let round = game.state().round;
-        if game.state().cards_total() > orig_cards {
-            panic!(
-                "Cards are procreating! {} vs orig {}",
-                game.state().cards_total(),
-                orig_cards
-            );
-        }
         let agent = &mut agents[game.state().player_turn];
         let draw_action = agent.select_draw_action(game.state());
         played_actions.push((round, player, Action::Draw(draw_action)));
@@ -52,19 +54,7 @@ fn run_match(teams: &[&[AgentType]], seed: u64) -> Result<(usize, Vec<PlayedActi
 
         // play until noop
         'player_plays: loop {
-            if game.state().cards_total() > orig_cards {
-                panic!(
-                    "Cards are procreating! {} vs orig {}",
-                    game.state().cards_total(),
-                    orig_cards
-                );
-            }
-
-            let available_actions = PlayAction::enumerate(
-                &game.current_team().played_runs,
-                &game.current_player().hand,
-                moves_allowed,
-            );
+            let available_actions = game.play_actions(moves_allowed);
             let selected_action = agent.select_play_action(available_actions, game.state());
             played_actions.push((round, player, Action::Play(selected_action.clone())));
             if let PlayAction::MoveCard(_, _, _) = selected_action {
@@ -80,7 +70,7 @@ fn run_match(teams: &[&[AgentType]], seed: u64) -> Result<(usize, Vec<PlayedActi
             }
         }
 
-        let discard_action = agent.select_discard_action(&game.current_player().hand, game.state());
+        let discard_action = agent.select_discard_action(&game.discard_choices(), game.state());
         played_actions.push((round, player, Action::Discard(discard_action)));
         game.discard(discard_action)?;
         if let Finished(_) = game.phase() {
@@ -89,7 +79,7 @@ fn run_match(teams: &[&[AgentType]], seed: u64) -> Result<(usize, Vec<PlayedActi
     }
 
     if let Finished(winning_team) = game.phase() {
-        Ok((winning_team, played_actions))
+        Ok((winning_team, game.scoreboard(), played_actions))
     } else {
         panic!(
             "Game should not finish in other state than Finished, was {:?}",
@@ -98,66 +88,65 @@ fn run_match(teams: &[&[AgentType]], seed: u64) -> Result<(usize, Vec<PlayedActi
     }
 }
 
-#[test]
-fn run_dumb_vs_smart() -> Result<(), String> {
-    let team_agents = [
-        &[AgentType::Dumb, AgentType::Dumb][..],
-        &[AgentType::Smart, AgentType::Smart][..],
-    ];
+/// One-sided 95 % for a standard normal
+const Z_95: f64 = 1.645;
 
-    const NUM_ROUNDS: usize = 100;
+/// Plays seeded games between two teams and asserts that `better_team` wins significantly
+/// more than half of them
+fn assert_beats(
+    team_agents: &[&[AgentType]; 2],
+    better_team: usize,
+    num_games: usize,
+) -> Result<(), String> {
     let mut team_wins = vec![0; team_agents.len()];
 
-    for i in 0..NUM_ROUNDS {
-        let (winner, _actions) = run_match(&team_agents[..], i as u64)?;
+    for i in 0..num_games {
+        let (winner, _scores, _actions) = run_match(&team_agents[..], i as u64)?;
         team_wins[winner] += 1;
     }
 
-    println!("Played {} rounds: {:?}", NUM_ROUNDS, team_agents);
+    println!("Played {} rounds: {:?}", num_games, team_agents);
     for (i, team_win) in team_wins.iter().enumerate() {
         println!(
             " - Team {} ({:?}) - {} wins ({} %)",
             i,
             team_agents[i],
             team_win,
-            (*team_win as f64) * 100f64 / NUM_ROUNDS as f64
+            (*team_win as f64) * 100f64 / num_games as f64
         );
     }
 
-    // CHANGE DETECTOR test
-    assert_eq!(31, team_wins[0]); // dumb % vs smart
+    // normal approximation of the binomial test against a fair coin
+    let n = num_games as f64;
+    let z = (team_wins[better_team] as f64 - n / 2.0) / (n / 4.0).sqrt();
+    assert!(
+        z >= Z_95,
+        "Team {} ({:?}) is not significantly better: {} of {} wins, z = {:.2}",
+        better_team,
+        team_agents[better_team],
+        team_wins[better_team],
+        num_games,
+        z
+    );
     Ok(())
 }
 
+#[test]
+fn run_dumb_vs_smart() -> Result<(), String> {
+    let team_agents = [
+        &[AgentType::Dumb, AgentType::Dumb][..],
+        &[AgentType::Smart, AgentType::Smart][..],
+    ];
+    assert_beats(&team_agents, 1, 100)
+}
+
 #[test]
 fn run_smart_vs_max() -> Result<(), String> {
     let team_agents = [
         &[AgentType::Smart, AgentType::Smart][..],
         &[AgentType::Max, AgentType::Max][..],
     ];
-
-    const NUM_ROUNDS: usize = 100;
-    let mut team_wins = vec![0; team_agents.len()];
-
-    for i in 0..NUM_ROUNDS {
-        let (winner, _actions) = run_match(&team_agents[..], i as u64)?;
-        team_wins[winner] += 1;
-    }
-
-    println!("Played {} rounds: {:?}", NUM_ROUNDS, team_agents);
-    for (i, team_win) in team_wins.iter().enumerate() {
-        println!(
-            " - Team {} ({:?}) - {} wins ({} %)",
-            i,
-            team_agents[i],
-            team_win,
-            (*team_win as f64) * 100f64 / NUM_ROUNDS as f64
-        );
-    }
-
-    // CHANGE DETECTOR test
-    assert_eq!(61, team_wins[0]); // smart % vs max
-    Ok(())
+    assert_beats(&team_agents, 0, 100)
 }
 
 #[test]
@@ -166,29 +155,7 @@ fn run_smartmax_vs_max() -> Result<(), String> {
         &[AgentType::Smart, AgentType::Max][..],
         &[AgentType::Max, AgentType::Max][..],
     ];
-
-    const NUM_ROUNDS: usize = 100;
-    let mut team_wins = vec![0; team_agents.len()];
-
-    for i in 0..NUM_ROUNDS {
-        let (winner, _actions) = run_match(&team_agents[..], i as u64)?;
-        team_wins[winner] += 1;
-    }
-
-    println!("Played {} rounds: {:?}", NUM_ROUNDS, team_agents);
-    for (i, team_win) in team_wins.iter().enumerate() {
-        println!(
-            " - Team {} ({:?}) - {} wins ({} %)",
-            i,
-            team_agents[i],
-            team_win,
-            (*team_win as f64) * 100f64 / NUM_ROUNDS as f64
-        );
-    }
-
-    // CHANGE DETECTOR test
-    assert_eq!(65, team_wins[0]); // smart max % vs max
-    Ok(())
+    assert_beats(&team_agents, 0, 100)
 }
 
 #[test]
@@ -197,58 +164,162 @@ fn run_smartmax_vs_smart() -> Result<(), String> {
         &[AgentType::Smart, AgentType::Max][..],
         &[AgentType::Smart, AgentType::Smart][..],
     ];
+    assert_beats(&team_agents, 1, 100)
+}
 
-    const NUM_ROUNDS: usize = 100;
-    let mut team_wins = vec![0; team_agents.len()];
+#[test]
+fn run_random_vs_max() -> Result<(), String> {
+    let team_agents = [
+        &[AgentType::SeededRandom(0), AgentType::SeededRandom(0)][..],
+        &[AgentType::Max, AgentType::Max][..],
+    ];
+    assert_beats(&team_agents, 1, 100)
+}
 
-    for i in 0..NUM_ROUNDS {
-        let (winner, _actions) = run_match(&team_agents[..], i as u64)?;
-        team_wins[winner] += 1;
+/// A game as text, one action per line, to be compared with a golden record
+fn match_record(teams: &[&[AgentType]], seed: u64) -> Result<String, String> {
+    let (winner, scores, actions) = run_match(teams, seed)?;
+    let mut record = format!("Teams: {:?}\nSeed: {}\n", teams, seed);
+    for (round, player, action) in actions.iter() {
+        let action = match action {
+            Action::Draw(draw) => draw.to_string(),
+            Action::Play(play) => play.to_string(),
+            Action::Discard(discard) => discard.to_string(),
+        };
+        record.push_str(&format!("{} P{}: {}\n", round, player, action));
     }
+    record.push_str(&format!(
+        "Winner: team {}, scoreboard: {:?}\n",
+        winner, scores
+    ));
+    Ok(record)
+}
 
-    println!("Played {} rounds: {:?}", NUM_ROUNDS, team_agents);
-    for (i, team_win) in team_wins.iter().enumerate() {
-        println!(
-            " - Team {} ({:?}) - {} wins ({} %)",
-            i,
-            team_agents[i],
-            team_win,
-            (*team_win as f64) * 100f64 / NUM_ROUNDS as f64
+/// Compares the record with tests/golden/`name`.txt, or writes it there when BURRACO_BLESS
+/// is set, so changes to the rules or the agents show up as a reviewable diff
+fn check_golden(name: &str, record: &str) -> Result<(), String> {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.txt", name));
+    if env::var_os("BURRACO_BLESS").is_some() {
+        return fs::write(&path, record).map_err(|e| format!("{}: {}", path.display(), e));
+    }
+
+    let golden = fs::read_to_string(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+    let expected: Vec<&str> = golden.lines().collect();
+    let actual: Vec<&str> = record.lines().collect();
+    if let Some(i) =
+        (0..expected.len().max(actual.len())).find(|i| expected.get(*i) != actual.get(*i))
+    {
+        panic!(
+            "Replay differs from {} at line {}\n expected: {}\n actual:   {}\n\
+             rerun with BURRACO_BLESS=1 to update it, and review the diff",
+            path.display(),
+            i + 1,
+            expected.get(i).unwrap_or(&"<end>"),
+            actual.get(i).unwrap_or(&"<end>")
         );
     }
+    Ok(())
+}
 
-    // CHANGE DETECTOR test
-    assert_eq!(52, team_wins[0]); // smart max % vs smart
+#[test]
+fn golden_replays() -> Result<(), String> {
+    let replays = [
+        (
+            "dumb_vs_smart",
+            [
+                &[AgentType::Dumb, AgentType::Dumb][..],
+                &[AgentType::Smart, AgentType::Smart][..],
+            ],
+            0,
+        ),
+        (
+            "smartmax_vs_max",
+            [
+                &[AgentType::Smart, AgentType::Max][..],
+                &[AgentType::Max, AgentType::Max][..],
+            ],
+            1,
+        ),
+        (
+            "random_vs_max",
+            [
+                &[AgentType::SeededRandom(0), AgentType::SeededRandom(0)][..],
+                &[AgentType::Max, AgentType::Max][..],
+            ],
+            2,
+        ),
+    ];
+    for (name, team_agents, seed) in replays.iter() {
+        check_golden(name, &match_record(&team_agents[..], *seed)?)?;
+    }
     Ok(())
 }
 
 #[test]
-fn run_random_vs_max() -> Result<(), String> {
+fn run_lookahead_vs_smart() -> Result<(), String> {
     let team_agents = [
-        &[AgentType::SeededRandom(0), AgentType::SeededRandom(0)][..],
-        &[AgentType::Max, AgentType::Max][..],
+        &[AgentType::Lookahead, AgentType::Lookahead][..],
+        &[AgentType::Smart, AgentType::Smart][..],
+    ];
+    assert_beats(&team_agents, 0, 100)
+}
+
+#[test]
+fn run_weighted_vs_smart() -> Result<(), String> {
+    let weighted = AgentType::Weighted(Weights::default());
+    let team_agents = [
+        &[weighted.clone(), weighted][..],
+        &[AgentType::Smart, AgentType::Smart][..],
     ];
+    assert_beats(&team_agents, 0, 100)
+}
 
-    const NUM_ROUNDS: usize = 100;
-    let mut team_wins = vec![0; team_agents.len()];
+#[test]
+fn run_expert_vs_smart() -> Result<(), String> {
+    let team_agents = [
+        &[AgentType::Expert, AgentType::Expert][..],
+        &[AgentType::Smart, AgentType::Smart][..],
+    ];
+    assert_beats(&team_agents, 0, 100)
+}
 
-    for i in 0..NUM_ROUNDS {
-        let (winner, _actions) = run_match(&team_agents[..], i as u64)?;
-        team_wins[winner] += 1;
+#[test]
+fn run_linear_vs_smart() -> Result<(), String> {
+    let linear = AgentType::Linear(Arc::new(LinearPolicy::default()));
+    let team_agents = [
+        &[linear.clone(), linear][..],
+        &[AgentType::Smart, AgentType::Smart][..],
+    ];
+    assert_beats(&team_agents, 0, 100)
+}
+
+/// Each difficulty wins significantly more often against the smart agent than the one below it,
+/// by the normal approximation of a two-proportion test over the same seeded games
+#[test]
+fn run_difficulty_calibration() -> Result<(), String> {
+    const NUM_GAMES: u64 = 100;
+    let win_rates = Difficulty::ALL
+        .iter()
+        .map(|level| level.measure_win_rate(0..NUM_GAMES))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (level, win_rate) in Difficulty::ALL.iter().zip(win_rates.iter()) {
+        println!("{}: {:.1} % wins against smart", level, win_rate * 100.0);
     }
 
-    println!("Played {} rounds: {:?}", NUM_ROUNDS, team_agents);
-    for (i, team_win) in team_wins.iter().enumerate() {
-        println!(
-            " - Team {} ({:?}) - {} wins ({} %)",
-            i,
-            team_agents[i],
-            team_win,
-            (*team_win as f64) * 100f64 / NUM_ROUNDS as f64
+    for (levels, rates) in Difficulty::ALL.windows(2).zip(win_rates.windows(2)) {
+        let pooled = (rates[0] + rates[1]) / 2.0;
+        let z = (rates[1] - rates[0]) / (pooled * (1.0 - pooled) * 2.0 / NUM_GAMES as f64).sqrt();
+        assert!(
+            z >= Z_95,
+            "{} ({:.2} wins) is not significantly harder than {} ({:.2} wins), z = {:.2}",
+            levels[1],
+            rates[1],
+            levels[0],
+            rates[0],
+            z
         );
     }
-
-    // CHANGE DETECTOR test
-    assert_eq!(41, team_wins[0]); // smart % vs max
     Ok(())
 }
@@ -0,0 +1,246 @@
+use burraco::actions::BurracoGame;
+use burraco::actions::DiscardAction;
+use burraco::actions::DrawAction;
+use burraco::actions::GamePhase;
+use burraco::actions::PlayAction;
+use burraco::model::Append;
+use burraco::model::BurracoState;
+use burraco::model::Card;
+use burraco::model::Cards;
+use burraco::model::Rank;
+use burraco::model::Run;
+use burraco::model::Suit;
+use burraco::model::SUITS;
+use burraco::model::SUIT_RANK;
+use burraco::rules::DealRules;
+use burraco::rules::DealStyle;
+use burraco::rules::DiscardRules;
+use burraco::rules::OpenPileDraw;
+use burraco::rules::Rules;
+use burraco::rules::TableVariant;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::select;
+
+/// a random open pile draw may go back and forth forever
+const MAX_TURNS: usize = 300;
+
+fn card() -> impl Strategy<Value = Card> {
+    prop_oneof![
+        1 => Just(Card(Suit::Jokers, Rank::Joker)),
+        26 => (select(&SUITS[..]), select(&SUIT_RANK[..])).prop_map(|(s, r)| Card(s, r)),
+    ]
+}
+
+fn cards(max_len: usize) -> impl Strategy<Value = Cards> {
+    vec(card(), 0..max_len).prop_map(Cards)
+}
+
+/// Natural sequences, some with a wildcard in the middle
+fn sequence_run() -> impl Strategy<Value = Run> {
+    (
+        select(&SUITS[..]),
+        0..11usize,
+        3..14usize,
+        any::<Option<usize>>(),
+    )
+        .prop_filter_map("valid sequence", |(suit, start, len, wildcard_at)| {
+            let end = (start + len).min(SUIT_RANK.len());
+            let mut cards: Vec<Card> = SUIT_RANK[start..end]
+                .iter()
+                .map(|rank| Card(suit, *rank))
+                .collect();
+            if let Some(at) = wildcard_at {
+                let at = 1 + at % (cards.len() - 2);
+                cards[at] = Card(Suit::Jokers, Rank::Joker);
+            }
+            Run::build_sequence_run(Cards(cards)).ok()
+        })
+}
+
+fn group_run() -> impl Strategy<Value = Run> {
+    (
+        select(&SUIT_RANK[1..]),
+        vec(select(&SUITS[..]), 3..8),
+        any::<bool>(),
+    )
+        .prop_filter_map("valid group", |(rank, suits, with_wildcard)| {
+            let mut cards: Vec<Card> = suits.iter().map(|suit| Card(*suit, rank)).collect();
+            if with_wildcard {
+                cards.push(Card(Suit::Jokers, Rank::Joker));
+            }
+            Run::build_group_run(Cards(cards)).ok()
+        })
+}
+
+fn run() -> impl Strategy<Value = Run> {
+    prop_oneof![sequence_run(), group_run()]
+}
+
+fn rules() -> impl Strategy<Value = Rules> {
+    (
+        select(vec![
+            TableVariant::OneVsOne,
+            TableVariant::OneVsOneVsOne,
+            TableVariant::TwoVsTwo,
+        ]),
+        any::<bool>(),
+        select(vec![
+            OpenPileDraw::WholePile,
+            OpenPileDraw::TopCardOnly,
+            OpenPileDraw::WholePileMustUseTop,
+        ]),
+        any::<bool>(),
+        select(vec![DealStyle::Packets, DealStyle::Alternating]),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(table, brazilian, open_pile_draw, federation, style, cut)| {
+                let rules = if brazilian {
+                    Rules::brazilian()
+                } else {
+                    Rules::default()
+                };
+                Rules {
+                    table,
+                    open_pile_draw,
+                    discard: if federation {
+                        DiscardRules::federation()
+                    } else {
+                        DiscardRules::default()
+                    },
+                    deal: DealRules {
+                        style,
+                        cut,
+                        ..DealRules::default()
+                    },
+                    ..rules
+                }
+            },
+        )
+}
+
+fn state() -> impl Strategy<Value = BurracoState> {
+    (rules(), any::<u64>())
+        .prop_map(|(rules, seed)| BurracoState::init_with_rules(rules, Some(seed)))
+}
+
+fn is_finished(game: &BurracoGame) -> bool {
+    matches!(game.phase(), GamePhase::Finished(_))
+}
+
+/// Plays a game picking the actions by the choices, `check` sees the actions before every play
+fn play_game(
+    state: BurracoState,
+    choices: &[usize],
+    mut check: impl FnMut(&BurracoGame, &[(PlayAction, i32)]),
+) -> Result<BurracoGame, String> {
+    let mut game = BurracoGame::from(state);
+    let mut choices = choices.iter().cycle();
+    let mut choose = |n: usize| choices.next().map(|c| c % n).unwrap_or(0);
+
+    for _turn in 0..MAX_TURNS {
+        let draws = DrawAction::allowed(game.state());
+        game.draw(draws[choose(draws.len())])?;
+        game.check_invariants()?;
+        if is_finished(&game) {
+            break;
+        }
+
+        let mut moves_allowed = game.current_team().played_runs.len();
+        while game.phase() == GamePhase::Play {
+            let actions = game.play_actions(moves_allowed);
+            check(&game, &actions);
+            let (action, _score) = actions[choose(actions.len())].clone();
+            if let PlayAction::MoveCard(_, _, _) = action {
+                moves_allowed -= 1;
+            }
+            game.play(action)?;
+            game.check_invariants()?;
+        }
+        if is_finished(&game) {
+            break;
+        }
+
+        let discards = game.discard_choices();
+        game.discard(DiscardAction(discards[choose(discards.len())]))?;
+        game.check_invariants()?;
+        if is_finished(&game) {
+            break;
+        }
+    }
+    Ok(game)
+}
+
+proptest! {
+    #[test]
+    fn card_parses_its_display(card in card()) {
+        prop_assert_eq!(Ok(Cards(vec![card])), Cards::of(&card.to_string()));
+    }
+
+    #[test]
+    fn sort_keeps_the_cards(cards in cards(30)) {
+        let mut sorted = cards.clone();
+        sorted.sort();
+        prop_assert_eq!(cards.deck_counts(), sorted.deck_counts());
+        let mut sorted_again = sorted.clone();
+        sorted_again.sort();
+        prop_assert_eq!(sorted, sorted_again);
+    }
+
+    #[test]
+    fn append_then_validate_is_idempotent(
+        run in run(),
+        cards in cards(3),
+        top in any::<bool>(),
+    ) {
+        prop_assert_eq!(Ok(run.clone()), run.append(&Cards(vec![]), Append::Top));
+        let append_to = if top { Append::Top } else { Append::Bottom };
+        if let Ok(appended) = run.append(&cards, append_to) {
+            prop_assert_eq!(run.cards().len() + cards.len(), appended.cards().len());
+            prop_assert_eq!(Ok(appended.clone()), appended.append(&Cards(vec![]), Append::Top));
+            prop_assert_eq!(Ok(appended.clone()), appended.append(&Cards(vec![]), Append::Bottom));
+        }
+    }
+
+    #[test]
+    fn dealt_state_is_consistent(state in state()) {
+        prop_assert_eq!(Ok(()), state.check_cards());
+        let hand_size = state.rules.table.hand_size();
+        for team in state.teams.iter() {
+            for player in team.players.iter() {
+                prop_assert_eq!(hand_size, player.hand.len());
+            }
+        }
+        prop_assert_eq!(Ok(()), BurracoGame::from(state).check_invariants());
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn random_games_keep_the_invariants(
+        state in state(),
+        choices in vec(any::<usize>(), 0..200),
+    ) {
+        let game = play_game(state, &choices, |_game, _actions| {})
+            .map_err(TestCaseError::fail)?;
+        prop_assert_eq!(Ok(()), game.state().check_cards());
+    }
+
+    #[test]
+    fn enumerated_actions_are_accepted(
+        state in state(),
+        choices in vec(any::<usize>(), 0..200),
+    ) {
+        play_game(state, &choices, |game, actions| {
+            for (action, _score) in actions.iter() {
+                let mut game = game.clone();
+                assert_eq!(Ok(()), game.play(action.clone()), "{:?}", action);
+            }
+        })
+        .map_err(TestCaseError::fail)?;
+    }
+}
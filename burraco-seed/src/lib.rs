@@ -11,8 +11,19 @@ use burraco::actions::DrawAction;
 use burraco::actions::GamePhase;
 use burraco::actions::PlayAction;
 use burraco::agent::*;
+use burraco::budget::decide_discard;
+use burraco::budget::decide_draw;
+use burraco::budget::decide_play;
+use burraco::budget::Budget;
+use burraco::budget::Decision;
+use burraco::hint::hints;
+use burraco::hint::HintConfig;
 use burraco::model::BurracoState;
 use burraco::model::Cards;
+use burraco::model::Run;
+use burraco::rules::Rules;
+use burraco::rules::TableVariant;
+use burraco::tournament::offer_close_query;
 use seed::prelude::*;
 
 mod view;
@@ -24,6 +35,23 @@ mod view;
 /// if manual player is used, it has this player number
 const MANUAL_PLAYER: usize = 0;
 
+/// wall time for an automatic player to decide, so a slow agent does not freeze the page
+const DECISION_MILLIS: f64 = 500.0;
+
+/// `Instant` is not available in the browser
+fn decision_budget() -> Budget {
+    Budget::time_with(DECISION_MILLIS, js_sys::Date::now)
+}
+
+/// marks the moves an automatic player made by default, having run out of time
+fn fell_back_note(fell_back: bool) -> &'static str {
+    if fell_back {
+        " (out of time, default move)"
+    } else {
+        ""
+    }
+}
+
 // `init` describes what should happen when your app started.
 fn init(_: Url, _orders: &mut impl Orders<RootMsg>) -> Model {
     // log!(format!("game model is some: {}", model.game_model.is_some()));
@@ -31,8 +59,8 @@ fn init(_: Url, _orders: &mut impl Orders<RootMsg>) -> Model {
     Model {
         game_model: None,
         init_options: InitOptions {
-            num_teams: 2,
-            num_team_players: 2,
+            table: TableVariant::TwoVsTwo,
+            brazilian: false,
             enable_manual_player: true,
             agents: vec![
                 AgentType::Smart,
@@ -61,8 +89,16 @@ pub struct GameModel {
     enable_manual_player: bool,
     agents: Vec<Box<dyn BurracoAgent>>,
     last_move: String,
+    /// why the automatic player made the last move, if its agent tells
+    last_explanation: Option<Explanation>,
+    /// asked for by the manual player, until the next move
+    hints: Vec<String>,
     draw_choices: Vec<String>,
     play_choices: Vec<(usize, String)>,
+    /// the valid actions for the current card selection, indexed by `Msg::Play`
+    play_actions: Vec<PlayAction>,
+    /// why the current card selection cannot be played in other ways
+    rejections: Vec<String>,
     discard_choices: Vec<String>,
     curr_player_moves_allowed: usize,
     selected_cards: HashSet<usize>,
@@ -73,10 +109,10 @@ impl GameModel {
         if self.is_manual_turn() {
             match self.game.phase() {
                 GamePhase::Draw => {
-                    self.draw_choices.append(&mut vec![
-                        format!("{}", DrawAction::DrawOpen),
-                        format!("{}", DrawAction::DrawPile),
-                    ]);
+                    self.draw_choices = DrawAction::allowed(self.game.state())
+                        .iter()
+                        .map(|a| format!("{}", a))
+                        .collect();
 
                     self.play_choices.clear();
                     self.discard_choices.clear();
@@ -84,25 +120,59 @@ impl GameModel {
 
                 GamePhase::Play => {
                     self.play_choices.clear();
-                    let actions = PlayAction::enumerate(
+                    self.play_actions.clear();
+                    self.rejections.clear();
+
+                    let hand = &self.game.current_player().hand;
+                    let mut selected =
+                        Cards(self.selected_cards.iter().map(|i| hand[*i]).collect());
+                    selected.sort();
+
+                    let rules = &self.game.state().rules.run;
+                    let mut candidates = PlayAction::candidates_for(
+                        rules,
                         &self.game.current_team().played_runs,
-                        &self.game.current_player().hand,
-                        1,
+                        &selected,
                     );
-
-                    let mut action_strs: Vec<_> = actions
+                    if selected.is_empty() {
+                        let moves = PlayAction::enumerate(
+                            rules,
+                            &self.game.current_team().played_runs,
+                            hand,
+                            1,
+                        )
                         .into_iter()
-                        .enumerate()
-                        .filter(|(_i, (a, _))| {
-                            filter_play_action(
-                                a,
-                                &self.selected_cards,
-                                &self.game.current_player().hand,
-                            )
-                        })
-                        .map(|(i, (a, _d_score))| (i, format!("{}", a)))
-                        .collect();
-                    self.play_choices.append(&mut action_strs);
+                        .map(|(a, _d_score)| a)
+                        .filter(|a| matches!(a, PlayAction::MoveCard(_, _, _)));
+                        candidates.extend(moves);
+                    } else if selected.len() >= 3
+                        && Run::arrangements_with(&selected, *rules).is_empty()
+                    {
+                        if let Err(reason) = Run::try_build_with(selected.clone(), *rules) {
+                            self.rejections.push(format!("New run: {}", reason));
+                        }
+                    }
+
+                    let mut rejections = Vec::new();
+                    for action in candidates {
+                        match self.game.validate(&action) {
+                            Ok(_outcome) => {
+                                self.play_choices
+                                    .push((self.play_actions.len(), format!("{}", action)));
+                                self.play_actions.push(action);
+                            }
+                            Err(e) => rejections.push(format!("{}: {}", action, e)),
+                        }
+                    }
+                    if self.play_actions.is_empty() {
+                        // only bother the player with reasons when nothing fits the selection
+                        self.rejections.append(&mut rejections);
+                    }
+                    if !selected.is_empty() {
+                        self.play_choices
+                            .push((self.play_actions.len(), format!("{}", PlayAction::Noop)));
+                        self.play_actions.push(PlayAction::Noop);
+                    }
 
                     self.draw_choices.clear();
                     self.discard_choices.clear();
@@ -111,11 +181,15 @@ impl GameModel {
                 GamePhase::Discard => {
                     if self.selected_cards.len() == 1 {
                         self.discard_choices.clear();
-                        self.discard_choices.push(format!(
-                            "Discard {}",
+                        self.rejections.clear();
+                        let action = DiscardAction(
                             self.game.current_player().hand
-                                [*self.selected_cards.iter().next().unwrap()]
-                        ));
+                                [*self.selected_cards.iter().next().unwrap()],
+                        );
+                        match self.game.validate_discard(&action) {
+                            Ok(_penalty) => self.discard_choices.push(format!("{}", action)),
+                            Err(reason) => self.rejections.push(reason.to_string()),
+                        }
                     }
 
                     self.draw_choices.clear();
@@ -149,8 +223,9 @@ pub enum RootMsg {
 
 #[derive(Clone, Debug)]
 pub struct InitOptions {
-    pub num_teams: usize,
-    pub num_team_players: usize,
+    pub table: TableVariant,
+    /// play Buraco with the Brazilian rules instead of Italian Burraco
+    pub brazilian: bool,
     pub enable_manual_player: bool,
     pub agents: Vec<AgentType>,
 }
@@ -158,10 +233,10 @@ pub struct InitOptions {
 #[derive(Clone, Debug)]
 pub enum InitMsg {
     Create,
-    SetNumTeams(usize),
-    SetPlayers(usize),
+    SetTable(TableVariant),
     SetAgent(usize, AgentType),
     FlipManual,
+    FlipBrazilian,
 }
 
 #[derive(Clone, Debug)]
@@ -170,6 +245,7 @@ pub enum Msg {
     Play(usize),
     Discard, // discard selected
     Select(usize),
+    Hint,
     Advance,
 }
 
@@ -184,10 +260,16 @@ fn update(msg: RootMsg, model: &mut Model, orders: &mut impl Orders<RootMsg>) {
     } else {
         match msg {
             RootMsg::Init(InitMsg::Create) => {
-                let state = BurracoState::init_with(
-                    model.init_options.num_teams,
-                    model.init_options.num_team_players,
-                );
+                let base = if model.init_options.brazilian {
+                    Rules::brazilian()
+                } else {
+                    Rules::default()
+                };
+                let rules = Rules {
+                    table: model.init_options.table,
+                    ..base
+                };
+                let state = BurracoState::init_with_rules(rules, None);
 
                 let game = BurracoGame::from(state);
 
@@ -198,11 +280,15 @@ fn update(msg: RootMsg, model: &mut Model, orders: &mut impl Orders<RootMsg>) {
                         .init_options
                         .agents
                         .iter()
-                        .map(|a| create_agent(*a))
+                        .map(|a| create_agent(a.clone()))
                         .collect(),
                     last_move: "".into(),
+                    last_explanation: None,
+                    hints: vec![],
                     draw_choices: vec![],
                     play_choices: vec![],
+                    play_actions: vec![],
+                    rejections: vec![],
                     discard_choices: vec![],
                     curr_player_moves_allowed: 0,
                     selected_cards: HashSet::new(),
@@ -211,11 +297,19 @@ fn update(msg: RootMsg, model: &mut Model, orders: &mut impl Orders<RootMsg>) {
                 game_model.update_choices();
                 model.game_model.replace(game_model);
             }
-            RootMsg::Init(InitMsg::SetNumTeams(num)) => model.init_options.num_teams = num,
-            RootMsg::Init(InitMsg::SetPlayers(num)) => model.init_options.num_team_players = num,
+            RootMsg::Init(InitMsg::SetTable(table)) => {
+                model.init_options.table = table;
+                model
+                    .init_options
+                    .agents
+                    .resize(table.num_players(), AgentType::Smart);
+            }
             RootMsg::Init(InitMsg::FlipManual) => {
                 model.init_options.enable_manual_player = !model.init_options.enable_manual_player
             }
+            RootMsg::Init(InitMsg::FlipBrazilian) => {
+                model.init_options.brazilian = !model.init_options.brazilian
+            }
             RootMsg::Init(InitMsg::SetAgent(idx, agent_type)) => {
                 model.init_options.agents[idx] = agent_type
             }
@@ -226,37 +320,6 @@ fn update(msg: RootMsg, model: &mut Model, orders: &mut impl Orders<RootMsg>) {
     }
 }
 
-fn filter_play_action(action: &PlayAction, selected_cards: &HashSet<usize>, hand: &Cards) -> bool {
-    if selected_cards.is_empty() {
-        return *action == PlayAction::Noop;
-    }
-    match action {
-        PlayAction::StartRun(run) => {
-            selected_cards.len() == 3
-                && selected_cards
-                    .iter()
-                    .all(|i| run.cards().iter().any(|c1| *c1 == hand[*i]))
-        }
-        PlayAction::AppendTop(_, cards) => {
-            selected_cards.len() == cards.len()
-                && selected_cards
-                    .iter()
-                    .all(|i| cards.iter().any(|c1| *c1 == hand[*i]))
-        }
-        PlayAction::AppendBottom(_, cards) => {
-            selected_cards.len() == cards.len()
-                && selected_cards
-                    .iter()
-                    .all(|i| cards.iter().any(|c1| *c1 == hand[*i]))
-        }
-        PlayAction::ReplaceWildcard(_, _, card) => {
-            selected_cards.len() == 1 && hand[*selected_cards.iter().next().unwrap()] == *card
-        }
-        PlayAction::MoveCard(_, _, _) => true,
-        PlayAction::Noop => true,
-    }
-}
-
 // `update` describes how to handle each `Msg`.
 fn update_game(
     msg: Msg,
@@ -275,20 +338,23 @@ fn update_game(
     if model.is_manual_turn() {
         match (model.game.phase(), msg) {
             (GamePhase::Draw, Msg::Draw(idx)) => {
-                let curr_move = [DrawAction::DrawOpen, DrawAction::DrawPile][idx];
+                let curr_move = DrawAction::allowed(model.game.state())[idx];
                 model.last_move =
                     format!("{} - Player {}", &curr_move, model.game.state().player_turn);
+                model.last_explanation = None;
+                model.hints.clear();
                 model.game.draw(curr_move).expect("valid draw action")
             }
             (GamePhase::Play, Msg::Play(idx)) => {
-                let actions = PlayAction::enumerate(
-                    &model.game.current_team().played_runs,
-                    &model.game.current_player().hand,
-                    7,
-                );
-                let curr_move = actions[idx].0.clone();
+                let curr_move = model
+                    .play_actions
+                    .get(idx)
+                    .cloned()
+                    .ok_or_else(|| format!("No play action with index {}", idx))?;
                 model.last_move =
                     format!("{} - Player {}", &curr_move, model.game.state().player_turn);
+                model.last_explanation = None;
+                model.hints.clear();
                 model.game.play(curr_move).expect("valid play action");
                 model.selected_cards.clear();
             }
@@ -298,9 +364,18 @@ fn update_game(
                 );
                 model.last_move =
                     format!("{} - Player {}", &curr_move, model.game.state().player_turn);
-                model.game.discard(curr_move).expect("valid discard");
+                model.last_explanation = None;
+                model.hints.clear();
+                model.game.discard(curr_move)?;
                 model.selected_cards.clear();
             }
+            (phase, Msg::Hint) => {
+                let view = model.game.state().player_view(MANUAL_PLAYER);
+                model.hints = hints(&view, phase, &HintConfig::default())?
+                    .into_iter()
+                    .map(|hint| format!("{}: {}", hint.action, hint.reason))
+                    .collect();
+            }
             (GamePhase::Play, Msg::Select(idx)) => {
                 if model.selected_cards.contains(&idx) {
                     model.selected_cards.remove(&idx);
@@ -317,34 +392,80 @@ fn update_game(
     } else if let Msg::Advance = msg {
         match model.game.phase() {
             GamePhase::Draw => {
-                let curr_move = model.agents[model.game.state().player_turn]
-                    .select_draw_action(model.game.state());
-                model.last_move =
-                    format!("{} - Player {}", &curr_move, model.game.state().player_turn);
+                let decision = decide_draw(
+                    model.agents[model.game.state().player_turn].as_mut(),
+                    model.game.state(),
+                    &mut decision_budget(),
+                );
+                let Decision {
+                    action: curr_move,
+                    fell_back,
+                    explanation,
+                } = decision;
+                model.last_move = format!(
+                    "{} - Player {}{}",
+                    &curr_move,
+                    model.game.state().player_turn,
+                    fell_back_note(fell_back)
+                );
+                model.last_explanation = explanation;
                 model.game.draw(curr_move).expect("valid draw action");
                 model.curr_player_moves_allowed = model.game.current_team().played_runs.len();
             }
             GamePhase::Play => {
-                let curr_move = model.agents[model.game.state().player_turn].select_play_action(
-                    PlayAction::enumerate(
-                        &model.game.current_team().played_runs,
-                        &model.game.current_player().hand,
-                        model.curr_player_moves_allowed,
-                    ),
+                let player_turn = model.game.state().player_turn;
+                if let Some(may_close) = offer_close_query(&mut model.game, &mut model.agents)? {
+                    // asking is a step of its own, the play follows on the next advance
+                    let answer = if may_close { "yes" } else { "no" };
+                    model.last_move = format!("May I close? {} - Player {}", answer, player_turn);
+                    model.last_explanation = None;
+                    model.update_choices();
+                    return Ok(());
+                }
+                let actions = model.game.play_actions(model.curr_player_moves_allowed);
+                let decision = decide_play(
+                    model.agents[model.game.state().player_turn].as_mut(),
+                    actions,
                     model.game.state(),
+                    &mut decision_budget(),
                 );
+                let Decision {
+                    action: curr_move,
+                    fell_back,
+                    explanation,
+                } = decision;
                 if let PlayAction::MoveCard(_, _, _) = curr_move {
                     model.curr_player_moves_allowed -= 1;
                 }
-                model.last_move =
-                    format!("{} - Player {}", &curr_move, model.game.state().player_turn);
+                model.last_move = format!(
+                    "{} - Player {}{}",
+                    &curr_move,
+                    model.game.state().player_turn,
+                    fell_back_note(fell_back)
+                );
+                model.last_explanation = explanation;
                 model.game.play(curr_move).expect("valid play action")
             }
             GamePhase::Discard => {
-                let curr_move = model.agents[model.game.state().player_turn]
-                    .select_discard_action(&model.game.current_player().hand, model.game.state());
-                model.last_move =
-                    format!("{} - Player {}", &curr_move, model.game.state().player_turn);
+                let hand = model.game.discard_choices();
+                let decision = decide_discard(
+                    model.agents[model.game.state().player_turn].as_mut(),
+                    &hand,
+                    model.game.state(),
+                    &mut decision_budget(),
+                );
+                let Decision {
+                    action: curr_move,
+                    fell_back,
+                    explanation,
+                } = decision;
+                model.last_move = format!(
+                    "{} - Player {}{}",
+                    &curr_move,
+                    model.game.state().player_turn,
+                    fell_back_note(fell_back)
+                );
+                model.last_explanation = explanation;
                 model.game.discard(curr_move).expect("valid discard");
                 model.curr_player_moves_allowed = 0;
             }
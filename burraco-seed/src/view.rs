@@ -6,12 +6,15 @@
 use crate::RootMsg;
 use burraco::actions::GamePhase;
 use burraco::agent::AgentType;
+use burraco::agent::Explanation;
+use burraco::difficulty::Difficulty;
 use burraco::model::BurracoState;
 use burraco::model::Card;
 use burraco::model::Cards;
 use burraco::model::Rank;
 use burraco::model::Run;
 use burraco::model::Suit;
+use burraco::rules::TableVariant;
 use seed::prelude::web_sys::Event;
 use seed::{prelude::*, *};
 
@@ -226,6 +229,21 @@ fn runs(runs: &[Run]) -> Node<RootMsg> {
     div![run_nodes]
 }
 
+/// The best of the alternatives an explanation ranks
+const EXPLAINED_ALTERNATIVES: usize = 5;
+
+fn explanation(explanation: &Explanation) -> Node<RootMsg> {
+    div![
+        "Why: ",
+        &explanation.reason,
+        ol![explanation
+            .ranked
+            .iter()
+            .take(EXPLAINED_ALTERNATIVES)
+            .map(|alternative| li![alternative.to_string()])],
+    ]
+}
+
 fn view_credits(_model: &Model) -> Vec<Node<RootMsg>> {
     raw![include_str!("../credits.html")]
 }
@@ -241,6 +259,7 @@ pub fn view_game(maybe_model: &Option<GameModel>) -> Node<RootMsg> {
         div![
             div!["Current player turn: ", model.game.state().player_turn],
             div!["Last move: ", &model.last_move],
+            model.last_explanation.as_ref().map(explanation),
             div![
                 C![
                     "playingCards",
@@ -287,8 +306,7 @@ pub fn view_game(maybe_model: &Option<GameModel>) -> Node<RootMsg> {
                         "flex" => "1"
                     },
                     "Pots",
-                    deck(&model.game.state().pot1),
-                    deck(&model.game.state().pot2)
+                    model.game.state().pots.iter().map(deck)
                 ],
                 div![
                     style! {
@@ -311,7 +329,11 @@ pub fn view_game(maybe_model: &Option<GameModel>) -> Node<RootMsg> {
                     IF!(!model.is_manual_turn() => button!["Advance other players", ev(Ev::Click, |_| RootMsg::Game(Advance)),]),
                     IF!(model.is_manual_turn() && model.game.phase() == GamePhase::Draw => div!["Draw action choices", draw_action_buttons(&model.draw_choices)]),
                     IF!(model.is_manual_turn() && model.game.phase() == GamePhase::Play => div!["Play action choices (select cards for more)", play_action_buttons(&model.play_choices)]),
+                    IF!(model.is_manual_turn() && model.game.phase() == GamePhase::Play && !model.rejections.is_empty() => div!["Not playable:", ul![model.rejections.iter().map(|r| li![r])]]),
                     IF!(model.is_manual_turn() && model.game.phase() == GamePhase::Discard => div!["Discard action choices", discard_action_buttons(&model.discard_choices)]),
+                    IF!(model.is_manual_turn() && model.game.phase() == GamePhase::Discard && !model.rejections.is_empty() => div!["Not allowed:", ul![model.rejections.iter().map(|r| li![r])]]),
+                    IF!(model.is_manual_turn() => button!["Hint", ev(Ev::Click, |_| RootMsg::Game(Hint)),]),
+                    IF!(model.is_manual_turn() && !model.hints.is_empty() => div!["Hints:", ol![model.hints.iter().map(|h| li![h])]]),
                 ],
             ],
         ]
@@ -324,35 +346,31 @@ pub fn view_init(model: &Model) -> Node<RootMsg> {
     div![
         h2!("Start a new game"),
         div![
-            p!["Number of teams: ", model.init_options.num_teams,],
-            p![input![
-                attrs! {
-                    At::AutoFocus => AtValue::None,
-                    At::Value => model.init_options.num_teams ,
-                    "type" => "range",
-                    "min" => 2,
-                    "max" => 3
-                },
-                input_ev(Ev::Input, move |value| RootMsg::Init(InitMsg::SetNumTeams(
-                    value.parse().unwrap()
-                )))
-            ]],
+            p![format!("Table: {:?}", model.init_options.table)],
+            p![[
+                TableVariant::OneVsOne,
+                TableVariant::OneVsOneVsOne,
+                TableVariant::TwoVsTwo,
+            ]
+            .iter()
+            .map(|t| {
+                button![
+                    format!("Play {:?}", t),
+                    ev(Ev::Click, move |_| RootMsg::Init(InitMsg::SetTable(*t))),
+                ]
+            })],
             p![
-                "Number players per team: ",
-                model.init_options.num_team_players
+                "Brazilian rules: ",
+                input![
+                    attrs! {
+                        At::Type => "checkbox",
+                        At::Checked => model.init_options.brazilian.as_at_value(),
+                    },
+                    input_ev(Ev::Input, move |_value| RootMsg::Init(
+                        InitMsg::FlipBrazilian
+                    ))
+                ]
             ],
-            p![input![
-                attrs! {
-                    At::AutoFocus => AtValue::None,
-                    At::Value => model.init_options.num_team_players ,
-                    "type" => "range",
-                    "min" => 1,
-                    "max" => 2 // TODO: reevaluate?
-                },
-                input_ev(Ev::Input, move |value| RootMsg::Init(InitMsg::SetPlayers(
-                    value.parse().unwrap()
-                )))
-            ]],
             p![
                 "Enable manual player: ",
                 input![
@@ -368,17 +386,36 @@ pub fn view_init(model: &Model) -> Node<RootMsg> {
             p!["Automatic player logic: "],
             model.init_options.agents.iter().enumerate().map(|(i, a)| {
                 let is_manual = model.init_options.enable_manual_player && i == 0;
+                let difficulty_buttons: Vec<Node<RootMsg>> = Difficulty::ALL
+                    .iter()
+                    .map(|level| {
+                        button![
+                            format!(
+                                "{} ({:.0}% wins vs Smart)",
+                                level,
+                                level.calibrated_win_rate() * 100.0
+                            ),
+                            ev(Ev::Click, move |_| RootMsg::Init(InitMsg::SetAgent(
+                                i,
+                                AgentType::Level(*level)
+                            ))),
+                        ]
+                    })
+                    .collect();
                 let agent_type_buttons: Vec<Node<RootMsg>> = [
                     AgentType::Dumb,
                     AgentType::Smart,
                     AgentType::Max,
+                    AgentType::Lookahead,
+                    AgentType::Expert,
                     AgentType::Random,
                 ]
                 .iter()
                 .map(|t| {
+                    let t = t.clone();
                     button![
                         format!("Use {:?}", t),
-                        ev(Ev::Click, move |_| RootMsg::Init(InitMsg::SetAgent(i, *t))),
+                        ev(Ev::Click, move |_| RootMsg::Init(InitMsg::SetAgent(i, t))),
                     ]
                 })
                 .collect();
@@ -386,8 +423,9 @@ pub fn view_init(model: &Model) -> Node<RootMsg> {
                     p![format!(
                         "Player {}: {:?}",
                         i,
-                        if is_manual { AgentType::Manual } else { *a }
+                        if is_manual { &AgentType::Manual } else { a }
                     )],
+                    IF!( !is_manual => p!["Difficulty: ", difficulty_buttons] ),
                     IF!( !is_manual => p![agent_type_buttons] )
                 ]
             }),